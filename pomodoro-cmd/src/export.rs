@@ -0,0 +1,33 @@
+use std::error::Error;
+use std::io::{self, Write};
+use chrono::NaiveDate;
+use pomodoro_core::calendar::Calendar;
+use pomodoro_core::config::Config;
+use pomodoro_core::export::{self, Format};
+use pomodoro_core::history::{History, Record};
+
+pub struct ExportOptions {
+    pub format: Format,
+    /// First day to export, included.
+    pub since: Option<NaiveDate>,
+    /// Last day to export, included.
+    pub until: Option<NaiveDate>,
+}
+
+pub fn run(options: ExportOptions) -> Result<(), Box<dyn Error>> {
+    let calendar = Calendar::from_config(&Config::load()?.stats)?;
+    let records: Vec<Record> = History::open()?.load()?
+        .into_iter()
+        .filter(|record| {
+            let day = calendar.day_of(&record.start);
+            options.since.is_none_or(|since| day >= since)
+                && options.until.is_none_or(|until| day <= until)
+        })
+        .collect();
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    export::write(&records, options.format, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use pomodoro_core::duration::format_duration;
+use pomodoro_core::history::{History, Outcome};
+use pomodoro_core::import::{self, Source};
+
+pub struct ImportOptions {
+    pub path: PathBuf,
+    pub source: Source,
+    /// Only print what would be imported.
+    pub dry_run: bool,
+}
+
+pub fn run(options: ImportOptions) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(&options.path)
+        .map_err(|e| format!("cannot read {}: {}", options.path.display(), e))?;
+    let history = History::open()?;
+    let import = import::import(&options.source, &text, &history.load()?)?;
+
+    for record in &import.records {
+        let outcome = match record.outcome {
+            Outcome::Completed => String::new(),
+            outcome => format!(" ({})", outcome.as_str()),
+        };
+        let tags: String = record.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+        let line = format!("{} {:>6} {}{} {}{}",
+                           record.start.format("%Y-%m-%d %H:%M"),
+                           format_duration(record.duration()),
+                           record.kind.as_str(), outcome,
+                           record.task.as_deref().unwrap_or_default(), tags);
+        println!("{}", line.trim_end());
+    }
+    if !options.dry_run {
+        // older records than the recorded ones go before them
+        history.insert(&import.records)?;
+    }
+
+    let verb = if options.dry_run { "to import" } else { "imported" };
+    println!("{} records {}, {} duplicates skipped, {} still running",
+             import.records.len(), verb, import.duplicates, import.running);
+    if options.dry_run {
+        println!("Dry run, {} is unchanged", history.path().display());
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::error::Error;
+use pomodoro_core::inbox::Inbox;
+use pomodoro_core::tasks::TaskList;
+
+/// Captures are numbered from 1, as printed by `pomodoro inbox`.
+pub enum InboxCommand {
+    Capture { text: String },
+    List,
+    Task { number: u32, estimate: Option<u32> },
+    Discard { number: u32 },
+}
+
+pub fn run(command: InboxCommand) -> Result<(), Box<dyn Error>> {
+    let mut inbox = Inbox::open()?;
+    match command {
+        InboxCommand::Capture { text } => {
+            inbox.capture(&text)?;
+            println!("Captured, {} in the inbox", inbox.captures().len());
+        }
+        InboxCommand::List => {
+            if inbox.is_empty() {
+                println!("The inbox is empty");
+            }
+            for line in lines(&inbox) {
+                println!("{}", line);
+            }
+        }
+        InboxCommand::Task { number, estimate } => {
+            let mut tasks = TaskList::open()?;
+            let id = inbox.into_task(index(number)?, &mut tasks, estimate)?;
+            if let Some(task) = tasks.get(id) {
+                println!("Added task {}: {}", task.id, task.name);
+            }
+        }
+        InboxCommand::Discard { number } => {
+            let capture = inbox.discard(index(number)?)?;
+            println!("Discarded: {}", capture.text);
+        }
+    }
+    Ok(())
+}
+
+/// The captures, one per line, shown by the timer during the breaks.
+pub fn lines(inbox: &Inbox) -> Vec<String> {
+    inbox.captures().iter().enumerate()
+        .map(|(i, capture)| format!("{:>3} {} {}",
+                                    i + 1, capture.captured.format("%H:%M"), capture.text))
+        .collect()
+}
+
+fn index(number: u32) -> Result<usize, &'static str> {
+    match number {
+        0 => Err("the notes are numbered from 1"),
+        n => Ok(n as usize - 1),
+    }
+}
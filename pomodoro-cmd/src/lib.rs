@@ -1,110 +1,923 @@
-use std::{thread, time};
+use std::{fmt, thread, time};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use time::Duration;
-use clap::{Arg, App};
+use clap::{Arg, App, AppSettings, ArgMatches, SubCommand};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Timelike};
+use pomodoro_core::config::{
+    Config as FileConfig, ConfigError as FileError, PromptsConfig, TimerConfig, TimerMode,
+};
+use pomodoro_core::duration::{format_duration, parse_duration, DurationError};
+use pomodoro_core::export::Format;
+use pomodoro_core::flowtime::Stopwatch;
+use pomodoro_core::history::{self, History, InterruptionKind, Outcome, Phase, Record, Running};
+use pomodoro_core::import::{CsvColumns, Source};
+use pomodoro_core::inbox::Inbox;
+use pomodoro_core::plan::{self as day_plan, format_time, parse_time, Block, PlanConfig, Slot};
+use pomodoro_core::schedule::{Schedule, ScheduleError, Step, StepKind};
+use pomodoro_core::tasks::{TaskError, TaskList};
+
+pub mod export;
+pub mod import;
+pub mod inbox;
+pub mod mark;
+pub mod plan;
+pub mod stats;
+pub mod task;
+
+const DEFAULT_NUM_POMODORO: &str = "4";
+const DEFAULT_DUR_POMODORO: &str = "25m";
+const DEFAULT_DUR_PAUSE: &str = "5m";
+const WAIT_HELP: &str = "Each pomodoro and pause waits for enter to start, unless auto_start_pomodoros or\nauto_start_breaks is set in [timer] of the config file.";
+
+/// What to do, picked by the subcommand.
+pub enum Command {
+    /// Run the timer, the default without a subcommand or with `start`.
+    Run(Config),
+    Stats(stats::StatsOptions),
+    Export(export::ExportOptions),
+    Import(import::ImportOptions),
+    Task(task::TaskCommand),
+    Mark(mark::MarkOptions),
+    Plan(plan::PlanOptions),
+    Inbox(inbox::InboxCommand),
+}
 
 pub struct Config {
     pub num_pomodoro: u32,
-    pub dur_pomodoro: u32,
-    pub dur_pause: u32,
+    pub dur_pomodoro: Duration,
+    pub dur_pause: Duration,
+    /// Id of the task the pomodoros are for.
+    pub task: Option<u32>,
+    /// Questions asked before and after each pomodoro.
+    pub prompts: PromptsConfig,
+    /// Timer settings of the config file or of the picked profile, for the
+    /// long breaks.
+    pub timer: TimerConfig,
+    /// Timetable of `plan --start`, each pomodoro waits for its start.
+    pub plan: Option<Vec<Slot>>,
+    /// [plan] of the config file, its calendar replaced by `--calendar`.
+    pub work_day: PlanConfig,
 }
 
-impl Config {
-    pub fn new() -> Result<Config, &'static str> {
+/// Wrong command line argument, `arg` is the long name of the option.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The shared config file can't be read.
+    File(FileError),
+    InvalidNumber { arg: &'static str, value: String },
+    InvalidDuration { arg: &'static str, value: String, source: DurationError },
+    ZeroDuration { arg: &'static str },
+    ZeroNumber { arg: &'static str },
+    InvalidDate { arg: &'static str, value: String },
+    InvalidTime { arg: &'static str, value: String },
+    /// Neither the option nor the config file set it.
+    MissingTime { arg: &'static str },
+    /// A `--column` that isn't `field=column` or names an unknown field.
+    InvalidColumn { value: String },
+    /// A `--column` given with another format than csv.
+    ColumnWithoutCsv { format: String },
+    UnknownProfile { name: String, known: Vec<String> },
+    InvalidSchedule { value: String, source: ScheduleError },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::File(e) => write!(f, "{}", e),
+            ConfigError::InvalidNumber { arg, value } =>
+                write!(f, "--{}: '{}' is not a number", arg, value),
+            ConfigError::InvalidDuration { arg, value, source } =>
+                write!(f, "--{}: '{}' is not a duration ({})", arg, value, source),
+            ConfigError::ZeroDuration { arg } =>
+                write!(f, "--{}: the duration can't be zero", arg),
+            ConfigError::ZeroNumber { arg } =>
+                write!(f, "--{}: must be at least 1", arg),
+            ConfigError::InvalidDate { arg, value } =>
+                write!(f, "--{}: '{}' is not a date (YYYY-MM-DD)", arg, value),
+            ConfigError::InvalidTime { arg, value } =>
+                write!(f, "--{}: '{}' is not a time (HH:MM)", arg, value),
+            ConfigError::MissingTime { arg } =>
+                write!(f, "--{}: no time given and no `{}` in [plan]", arg, arg),
+            ConfigError::InvalidColumn { value } =>
+                write!(f, "--column: '{}' is not a field=column mapping \
+                           (fields: start, end, duration, kind, outcome, task, tags)", value),
+            ConfigError::ColumnWithoutCsv { format } =>
+                write!(f, "--column: only a csv file has columns, not --format {}", format),
+            ConfigError::UnknownProfile { name, known } if known.is_empty() =>
+                write!(f, "--profile: no profile '{}', there are no [profiles] in the config", name),
+            ConfigError::UnknownProfile { name, known } =>
+                write!(f, "--profile: no profile '{}' (profiles: {})", name, known.join(", ")),
+            ConfigError::InvalidSchedule { value, source } =>
+                write!(f, "--schedule: '{}' is not a schedule ({})", value, source),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::File(e) => Some(e),
+            ConfigError::InvalidDuration { source, .. } => Some(source),
+            ConfigError::InvalidSchedule { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Command {
+    pub fn new() -> Result<Command, ConfigError> {
 
         let app_name = "pomodoro-cmd";
 
+        // the config file replaces the built-in defaults
+        let file = FileConfig::load().map_err(ConfigError::File)?;
+        let default_pomo = file.timer.pomodoros
+            .map_or_else(|| DEFAULT_NUM_POMODORO.to_string(), |n| n.to_string());
+        let default_dur = file.timer.pomodoro_duration
+            .map_or_else(|| DEFAULT_DUR_POMODORO.to_string(), format_duration);
+        let default_pause = file.timer.break_duration
+            .map_or_else(|| DEFAULT_DUR_PAUSE.to_string(), format_duration);
+
         let matches = App::new(app_name)
             .about("A minimal pomodoro timer.")
-            .arg(Arg::with_name("pomo")
-                     .short("t")
-                     .long("pomo")
-                     .takes_value(true)
-                     .help("Number of pomodoro"))
-            .arg(Arg::with_name("dur")
-                     .short("d")
-                     .long("dur")
-                     .takes_value(true)
-                     .help("Duration of each pomodoro (in minutes)"))
-            .arg(Arg::with_name("pause")
-                     .short("p")
-                     .long("pause")
-                     .takes_value(true)
-                     .help("Duration of each pause (in minutes)"))
+            .after_help(WAIT_HELP)
+            .args(&timer_args(&default_pomo, &default_dur, &default_pause))
+            .subcommand(SubCommand::with_name("start")
+                        .about("Runs the timer, the same as without a subcommand")
+                        .after_help(WAIT_HELP)
+                        .args(&timer_args(&default_pomo, &default_dur, &default_pause))
+                        .arg(Arg::with_name("task")
+                                 .long("task")
+                                 .takes_value(true)
+                                 .help("Id of the task to work on, see `task list`")))
+            .subcommand(SubCommand::with_name("plan")
+                        .about("Prints the pomodoros that fit in the day, around the lunch and the blocks of [plan]")
+                        .args(&timer_args(&default_pomo, &default_dur, &default_pause))
+                        .arg(Arg::with_name("from")
+                                 .long("from")
+                                 .takes_value(true)
+                                 .help("Start of the day (HH:MM), now by default"))
+                        .arg(Arg::with_name("until")
+                                 .long("until")
+                                 .takes_value(true)
+                                 .help("End of the day (HH:MM)"))
+                        .arg(Arg::with_name("calendar")
+                                 .long("calendar")
+                                 .takes_value(true)
+                                 .help("iCalendar file, or directory of them, whose events are busy"))
+                        .arg(Arg::with_name("start")
+                                 .long("start")
+                                 .help("Runs the timer, each pomodoro starting at its planned time")))
+            .subcommand(SubCommand::with_name("stats")
+                        .about("Prints the statistics of the recorded pomodoros")
+                        .arg(Arg::with_name("heatmap")
+                                 .long("heatmap")
+                                 .help("Prints the pomodoros per day of the last year"))
+                        .arg(Arg::with_name("estimates")
+                                 .long("estimates")
+                                 .help("Compares the estimated and actual pomodoros of the done tasks"))
+                        .arg(Arg::with_name("ascii")
+                                 .long("ascii")
+                                 .help("Draws only ascii characters")))
+            .subcommand(SubCommand::with_name("export")
+                        .about("Writes the recorded pomodoros and breaks to the standard output")
+                        .arg(Arg::with_name("format")
+                                 .short("f")
+                                 .long("format")
+                                 .takes_value(true)
+                                 .possible_values(&Format::NAMES)
+                                 .default_value("csv")
+                                 .help("Output format, ics only has the pomodoros"))
+                        .arg(Arg::with_name("since")
+                                 .long("since")
+                                 .takes_value(true)
+                                 .help("First day to export (YYYY-MM-DD)"))
+                        .arg(Arg::with_name("until")
+                                 .long("until")
+                                 .takes_value(true)
+                                 .help("Last day to export (YYYY-MM-DD)")))
+            .subcommand(SubCommand::with_name("import")
+                        .about("Adds the pomodoros recorded by other tools to the history")
+                        .arg(Arg::with_name("file")
+                                 .required(true)
+                                 .help("File to import"))
+                        .arg(Arg::with_name("format")
+                                 .short("f")
+                                 .long("format")
+                                 .takes_value(true)
+                                 .possible_values(&["csv", "timewarrior", "org"])
+                                 .default_value("csv")
+                                 .help("csv file, `timew export` output or org-mode CLOCK lines"))
+                        .arg(Arg::with_name("column")
+                                 .short("c")
+                                 .long("column")
+                                 .takes_value(true)
+                                 .multiple(true)
+                                 .number_of_values(1)
+                                 .help("Reads a field from a csv column, e.g. task=Description"))
+                        .arg(Arg::with_name("dry-run")
+                                 .short("n")
+                                 .long("dry-run")
+                                 .help("Prints the new records without saving them")))
+            .subcommand(SubCommand::with_name("mark")
+                        .about("Logs an interruption of the running pomodoro")
+                        .arg(Arg::with_name("kind")
+                                 .required(true)
+                                 .possible_values(&["internal", "external", "'", "-"])
+                                 .help("internal (') or external (-)"))
+                        .arg(Arg::with_name("note")
+                                 .multiple(true)
+                                 .help("What interrupted you")))
+            .subcommand(SubCommand::with_name("capture")
+                        .about("Adds a note to the inbox, to look at in the next break")
+                        .arg(Arg::with_name("text")
+                                 .required(true)
+                                 .multiple(true)
+                                 .help("What popped up")))
+            .subcommand(SubCommand::with_name("inbox")
+                        .about("Prints the captured notes, or turns them into tasks")
+                        .subcommand(SubCommand::with_name("task")
+                                    .about("Turns a note into a task")
+                                    .arg(Arg::with_name("number")
+                                             .required(true)
+                                             .help("Number of the note"))
+                                    .arg(Arg::with_name("estimate")
+                                             .short("e")
+                                             .long("estimate")
+                                             .takes_value(true)
+                                             .help("Pomodoros the task should take")))
+                        .subcommand(SubCommand::with_name("discard")
+                                    .about("Removes a note")
+                                    .arg(Arg::with_name("number")
+                                             .required(true)
+                                             .help("Number of the note"))))
+            .subcommand(SubCommand::with_name("task")
+                        .about("Manages the task list")
+                        .setting(AppSettings::SubcommandRequiredElseHelp)
+                        .subcommand(SubCommand::with_name("add")
+                                    .about("Adds a task")
+                                    .arg(Arg::with_name("name")
+                                             .required(true)
+                                             .multiple(true)
+                                             .help("Name of the task"))
+                                    .arg(Arg::with_name("estimate")
+                                             .short("e")
+                                             .long("estimate")
+                                             .takes_value(true)
+                                             .help("Pomodoros the task should take")))
+                        .subcommand(SubCommand::with_name("list")
+                                    .about("Prints the tasks not done yet")
+                                    .arg(Arg::with_name("all")
+                                             .short("a")
+                                             .long("all")
+                                             .help("Prints the done tasks too")))
+                        .subcommand(SubCommand::with_name("done")
+                                    .about("Marks a task as done")
+                                    .arg(Arg::with_name("id")
+                                             .required(true)
+                                             .help("Id of the task"))))
             .get_matches();
 
+        match matches.subcommand() {
+            ("stats", Some(sub)) => Ok(Command::Stats(stats::StatsOptions {
+                heatmap: sub.is_present("heatmap"),
+                ascii: sub.is_present("ascii"),
+                estimates: sub.is_present("estimates"),
+            })),
+            ("export", Some(sub)) => Ok(Command::Export(export::ExportOptions {
+                // clap only accepts the possible values
+                format: sub.value_of("format").unwrap_or_default().parse()
+                    .unwrap_or(Format::Csv),
+                since: sub.value_of("since").map(|v| date_arg("since", v)).transpose()?,
+                until: sub.value_of("until").map(|v| date_arg("until", v)).transpose()?,
+            })),
+            ("import", Some(sub)) => Ok(Command::Import(import::ImportOptions {
+                // the file is required
+                path: PathBuf::from(sub.value_of("file").unwrap_or_default()),
+                source: match sub.value_of("format") {
+                    Some(format) if format != "csv" && sub.is_present("column") =>
+                        return Err(ConfigError::ColumnWithoutCsv { format: format.to_string() }),
+                    Some("timewarrior") => Source::Timewarrior,
+                    Some("org") => Source::Org,
+                    _ => Source::Csv(columns_arg(sub.values_of("column").into_iter().flatten())?),
+                },
+                dry_run: sub.is_present("dry-run"),
+            })),
+            ("task", Some(sub)) => Ok(Command::Task(match sub.subcommand() {
+                ("add", Some(add)) => task::TaskCommand::Add {
+                    name: add.values_of("name").into_iter().flatten()
+                        .collect::<Vec<_>>().join(" "),
+                    estimate: add.value_of("estimate")
+                        .map(|v| number_arg("estimate", v)).transpose()?,
+                },
+                ("done", Some(done)) => task::TaskCommand::Done {
+                    id: number_arg("id", done.value_of("id").unwrap_or_default())?,
+                },
+                (_, list) => task::TaskCommand::List {
+                    all: list.is_some_and(|list| list.is_present("all")),
+                },
+            })),
+            ("mark", Some(sub)) => Ok(Command::Mark(mark::MarkOptions {
+                // clap only accepts the possible values
+                kind: sub.value_of("kind").unwrap_or_default().parse()
+                    .unwrap_or(InterruptionKind::Internal),
+                note: sub.values_of("note")
+                    .map(|note| note.collect::<Vec<_>>().join(" ")),
+            })),
+            ("capture", Some(sub)) => Ok(Command::Inbox(inbox::InboxCommand::Capture {
+                text: sub.values_of("text").into_iter().flatten()
+                    .collect::<Vec<_>>().join(" "),
+            })),
+            ("inbox", Some(sub)) => Ok(Command::Inbox(match sub.subcommand() {
+                ("task", Some(task)) => inbox::InboxCommand::Task {
+                    number: number_arg("number", task.value_of("number").unwrap_or_default())?,
+                    estimate: task.value_of("estimate")
+                        .map(|v| number_arg("estimate", v)).transpose()?,
+                },
+                ("discard", Some(discard)) => inbox::InboxCommand::Discard {
+                    number: number_arg("number", discard.value_of("number").unwrap_or_default())?,
+                },
+                _ => inbox::InboxCommand::List,
+            })),
+            ("plan", Some(sub)) => Ok(Command::Plan(plan::PlanOptions {
+                config: Config::from_matches(sub, &file)?,
+                from: match sub.value_of("from") {
+                    Some(value) => time_arg("from", value)?,
+                    // the next whole minute
+                    None => file.plan.from.unwrap_or_else(|| {
+                        let now = Local::now().time();
+                        now.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(now)
+                            + chrono::Duration::minutes(1)
+                    }),
+                },
+                until: match sub.value_of("until") {
+                    Some(value) => time_arg("until", value)?,
+                    None => file.plan.until.ok_or(ConfigError::MissingTime { arg: "until" })?,
+                },
+                busy: file.plan.busy(),
+                start: sub.is_present("start"),
+            })),
+            ("start", Some(sub)) => Ok(Command::Run(Config::from_matches(sub, &file)?)),
+            _ => Ok(Command::Run(Config::from_matches(&matches, &file)?)),
+        }
+    }
+}
 
-        let num_pomodoro = matches.value_of("pomo");
-        let num_pomodoro = match num_pomodoro {
-            Some(pomo) => {
-                match pomo.parse::<u32>() {
-                    Ok(n) => n,
-                    Err(_) => return Err("Error parsing number of pomodoros")
-                }
-            }
-            None => return Err("Error parsing number of pomodoros")
+impl Config {
+    fn from_matches(matches: &ArgMatches, file: &FileConfig) -> Result<Config, ConfigError> {
+        // every argument has a default value
+        let value = |arg| matches.value_of(arg).unwrap_or_default();
+        let mut timer = match matches.value_of("profile") {
+            Some(name) => file.profile(name).ok_or_else(|| ConfigError::UnknownProfile {
+                name: name.to_string(),
+                known: file.profiles.keys().cloned().collect(),
+            })?,
+            None => file.timer.clone(),
         };
+        // the options typed on the command line win over the profile
+        let typed = |arg| matches.occurrences_of(arg) > 0;
+        if typed("flowtime") {
+            timer.mode = Some(TimerMode::Flowtime);
+        }
+        let mut work_day = file.plan.clone();
+        if let Some(path) = matches.value_of("calendar") {
+            work_day.calendar = Some(PathBuf::from(path));
+        }
+        if let Some(text) = matches.value_of("schedule") {
+            timer.schedule = Some(text.parse().map_err(|source| ConfigError::InvalidSchedule {
+                value: text.to_string(),
+                source,
+            })?);
+        }
 
-        let dur_pomodoro = matches.value_of("dur");
-        let dur_pomodoro = match dur_pomodoro {
-            Some(dur) => {
-                match dur.parse::<u32>() {
-                    Ok(n) => n,
-                    Err(_) => return Err("Error parsing duration of pomodors")
-                }
-            }
-            None => return Err("Error parsing duration of pomodoros")
+        Ok(Config {
+                num_pomodoro: match timer.pomodoros {
+                    Some(n) if !typed("pomo") => n,
+                    _ => number_arg("pomo", value("pomo"))?,
+                },
+                dur_pomodoro: match timer.pomodoro_duration {
+                    Some(dur) if !typed("dur") => dur,
+                    _ => duration_arg("dur", value("dur"))?,
+                },
+                dur_pause: match timer.break_duration {
+                    Some(dur) if !typed("pause") => dur,
+                    _ => duration_arg("pause", value("pause"))?,
+                },
+                task: matches.value_of("task").map(|v| number_arg("task", v)).transpose()?,
+                prompts: file.prompts.clone(),
+                timer,
+                plan: None,
+                work_day,
+         })
+
+    }
+
+    /// Phases to walk through: the schedule, or the pomodoros each followed
+    /// by a break.
+    pub fn steps(&self) -> Vec<Step> {
+        match &self.timer.schedule {
+            Some(schedule) => schedule.steps().to_vec(),
+            None => Schedule::classic(self.num_pomodoro, self.dur_pomodoro, self.dur_pause,
+                                      &self.timer).steps().to_vec(),
+        }
+    }
+}
+
+// options of the timer, without a subcommand and after `start`
+fn timer_args<'a, 'b>(pomo: &'a str, dur: &'a str, pause: &'a str) -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("pomo")
+            .short("t")
+            .long("pomo")
+            .takes_value(true)
+            .default_value(pomo)
+            .help("Number of pomodoro"),
+        Arg::with_name("dur")
+            .short("d")
+            .long("dur")
+            .takes_value(true)
+            .default_value(dur)
+            .help("Duration of each pomodoro (25m, 1h30m, 45s or plain minutes)"),
+        Arg::with_name("pause")
+            .short("p")
+            .long("pause")
+            .takes_value(true)
+            .default_value(pause)
+            .help("Duration of each pause (5m, 90s or plain minutes)"),
+        Arg::with_name("profile")
+            .short("P")
+            .long("profile")
+            .takes_value(true)
+            .help("Timer settings of a profile of the config file, the other options win over it"),
+        Arg::with_name("flowtime")
+            .short("f")
+            .long("flowtime")
+            .help("Focus until you stop, with breaks proportional to the focus"),
+        Arg::with_name("schedule")
+            .short("s")
+            .long("schedule")
+            .takes_value(true)
+            .help("Custom sequence of phases instead of the pomodoros, like \"(25f 5b)x3 25f 20lb\""),
+    ]
+}
+
+fn number_arg(arg: &'static str, value: &str) -> Result<u32, ConfigError> {
+    match value.parse() {
+        Ok(0) => Err(ConfigError::ZeroNumber { arg }),
+        Ok(number) => Ok(number),
+        Err(_) => Err(ConfigError::InvalidNumber { arg, value: value.to_string() }),
+    }
+}
+
+fn time_arg(arg: &'static str, value: &str) -> Result<NaiveTime, ConfigError> {
+    parse_time(value).map_err(|_| ConfigError::InvalidTime { arg, value: value.to_string() })
+}
+
+fn date_arg(arg: &'static str, value: &str) -> Result<NaiveDate, ConfigError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| ConfigError::InvalidDate { arg, value: value.to_string() })
+}
+
+fn columns_arg<'a>(values: impl Iterator<Item = &'a str>) -> Result<CsvColumns, ConfigError> {
+    let mut columns = CsvColumns::default();
+    for value in values {
+        let invalid = || ConfigError::InvalidColumn { value: value.to_string() };
+        let (field, column) = value.split_once('=').ok_or_else(invalid)?;
+        columns.set(field.trim(), column.trim()).map_err(|_| invalid())?;
+    }
+    Ok(columns)
+}
+
+fn duration_arg(arg: &'static str, value: &str) -> Result<Duration, ConfigError> {
+    match parse_duration(value) {
+        Ok(duration) if duration == Duration::ZERO => Err(ConfigError::ZeroDuration { arg }),
+        Ok(duration) => Ok(duration),
+        Err(source) => Err(ConfigError::InvalidDuration {
+            arg,
+            value: value.to_string(),
+            source,
+        }),
+    }
+}
+
+/// How a phase of the timer ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    Finished,
+    Skipped,
+    Reset,
+}
+
+/// Counts down `time`, reading what is typed meanwhile: `s` skips the phase,
+/// `r` resets it and `+N` extends it by N minutes. Returns how the phase ended
+/// and the time added to it.
+pub fn timer(lines: &Receiver<String>, time: Duration, title: &str, notes: &[String])
+             -> (Ending, Duration) {
+    let start = time::Instant::now();
+    let mut length = time;
+    let mut extended = Duration::ZERO;
+    loop {
+        let elapsed = start.elapsed();
+        let Some(left) = length.checked_sub(elapsed).filter(|left| !left.is_zero()) else {
+            return (Ending::Finished, extended);
         };
+        print!("{esc}c", esc = 27 as char);
+        println!("{}", title);
+        println!("Elapsed min:{} sec:{}", elapsed.as_secs() / 60, elapsed.as_secs() % 60);
+        if !extended.is_zero() {
+            println!("Extended by {}", format_duration(extended));
+        }
+        for note in notes {
+            println!("{}", note);
+        }
+        println!("s skip, r reset, +N extend by N minutes, then enter");
+        let tick = left.min(Duration::from_secs(1));
+        match lines.recv_timeout(tick) {
+            Ok(line) => match line.trim() {
+                "s" => return (Ending::Skipped, extended),
+                "r" => return (Ending::Reset, extended),
+                text => if let Some(extra) = text.strip_prefix('+')
+                    .and_then(|extra| parse_duration(extra).ok())
+                    .filter(|extra| !extra.is_zero()) {
+                    length += extra;
+                    extended += extra;
+                },
+            },
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(tick),
+        }
+    }
+}
 
-        let dur_pause = matches.value_of("pause");
-        let dur_pause = match dur_pause {
-            Some(pause) => {
-                match pause.parse::<u32>() {
-                    Ok(n) => n,
-                    Err(_) => return Err("Error parsing duration of puase")
-                }
+// counts past the end of a phase until enter is pressed, returns the overtime
+fn overtime(lines: &Receiver<String>, title: &str) -> Duration {
+    let start = time::Instant::now();
+    loop {
+        let secs = start.elapsed().as_secs();
+        print!("{esc}c", esc = 27 as char);
+        println!("{} is over", title);
+        let overtime = format!("Overtime +{:02}:{:02}", secs / 60, secs % 60);
+        println!("{}, press enter to move on", magenta(&overtime));
+        match lines.recv_timeout(Duration::from_secs(1)) {
+            Err(RecvTimeoutError::Timeout) => (),
+            // nobody can move on by hand on a closed input
+            Ok(_) | Err(RecvTimeoutError::Disconnected) => return start.elapsed(),
+        }
+    }
+}
+
+// colors `text` on a terminal, unless NO_COLOR is set (https://no-color.org)
+fn magenta(text: &str) -> String {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if no_color || !io::stdout().is_terminal() {
+        return text.to_string();
+    }
+    format!("{esc}[35m{}{esc}[0m", text, esc = 27 as char)
+}
+
+// runs a phase until it ends or is skipped, a reset one is recorded as such
+// and starts over
+fn run_phase(lines: &Receiver<String>, history: &History, kind: Phase, time: Duration,
+             title: &str, notes: &[String], counts_overtime: bool) -> Record {
+    loop {
+        let start = start_phase(history, kind);
+        let (ending, extended) = timer(lines, time, title, notes);
+        let outcome = match ending {
+            Ending::Finished => Outcome::Completed,
+            Ending::Skipped => Outcome::Skipped,
+            Ending::Reset => {
+                let mut reset = Record::new(kind, start, Local::now(), Outcome::Reset);
+                reset.extended = Some(extended).filter(|extended| !extended.is_zero());
+                record(history, reset);
+                continue;
             }
-            None => return Err("Error parsing duration of pause")
         };
-        Ok(Config {
-                num_pomodoro,
-                dur_pomodoro,
-                dur_pause,
-         })
-
+        let over = (counts_overtime && ending == Ending::Finished)
+            .then(|| overtime(lines, title));
+        let mut phase = Record::new(kind, start, Local::now(), outcome);
+        phase.extended = Some(extended).filter(|extended| !extended.is_zero());
+        phase.overtime = over;
+        return phase;
     }
 }
 
-pub fn timer(time : u32, value: u32, tag: &str) {
-    // start timer
-    let duration = Duration::from_secs(1);
-    let mut elapsed_sec = 0;
-    let mut elapsed_min = 0;
-    for _t in 0..time*60{
+// counts the focus up until enter is pressed, for the flowtime mode
+fn run_focus(lines: &Receiver<String>, history: &History, title: &str) -> Record {
+    let start = start_phase(history, Phase::Pomodoro);
+    let mut stopwatch = Stopwatch::default();
+    stopwatch.start();
+    loop {
+        let secs = stopwatch.elapsed().as_secs();
         print!("{esc}c", esc = 27 as char);
-        println!("{} n° {}", tag, value);
-        println!("Elapsed min:{} sec:{}", elapsed_min, elapsed_sec);
-        elapsed_sec += 1;
-        elapsed_min += (elapsed_sec/60) % 60;
-        elapsed_sec %= 60;
-        thread::sleep(duration);
+        println!("{}", title);
+        println!("Focus min:{} sec:{}", secs / 60, secs % 60);
+        println!("Press enter to take a break");
+        match lines.recv_timeout(Duration::from_secs(1)) {
+            Err(RecvTimeoutError::Timeout) => (),
+            // nobody can stop by hand on a closed input
+            Ok(_) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let mut pomodoro = Record::new(Phase::Pomodoro, start, Local::now(), Outcome::Completed);
+    pomodoro.tags.push(String::from("flowtime"));
+    pomodoro
+}
+
+// tell `pomodoro mark` a phase is running and return its start
+fn start_phase(history: &History, kind: Phase) -> DateTime<Local> {
+    let running = Running { kind, start: Local::now() };
+    if let Err(e) = history.set_running(Some(&running)) {
+        eprintln!("Cannot save the running phase: {}", e);
+    }
+    running.start
+}
+
+// lines typed on the terminal, read by a thread so that a countdown can wait for them
+fn read_lines() -> Receiver<String> {
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    lines
+}
+
+// asks on the terminal, `None` for an empty answer or a closed input
+fn ask(lines: &Receiver<String>, question: &str) -> Option<String> {
+    print!("{} ", question);
+    io::stdout().flush().ok()?;
+    let answer = lines.recv().ok()?;
+    Some(answer.trim().to_string()).filter(|answer| !answer.is_empty())
+}
+
+// what got done and how focused it was, asked again until the rating is valid
+fn reflect(lines: &Receiver<String>, record: &mut Record) {
+    record.reflection = ask(lines, "What did you get done?");
+    record.focus_rating = loop {
+        match history::parse_focus_rating(&ask(lines, "Rate your focus 1-5:").unwrap_or_default()) {
+            Ok(rating) => break rating,
+            Err(e) => eprintln!("{}", e),
+        }
+    };
+}
+
+// save a finished phase, the timer goes on even if it can't be saved
+fn record(history: &History, mut record: Record) {
+    if record.kind == Phase::Pomodoro {
+        match history.take_marks(record.start) {
+            Ok(marks) => record.interruptions = marks,
+            Err(e) => eprintln!("Cannot read the interruptions: {}", e),
+        }
+    }
+    if let Err(e) = history.set_running(None).and_then(|_| history.append(&record)) {
+        eprintln!("Cannot save the history: {}", e);
+    }
+}
+
+// the captures to look at during a break, nothing if the inbox can't be read
+fn inbox_notes() -> Vec<String> {
+    match Inbox::open() {
+        Ok(inbox) if !inbox.is_empty() => {
+            let mut notes = vec![String::new(),
+                                 String::from("Inbox, see `pomodoro inbox --help`:")];
+            notes.extend(inbox::lines(&inbox));
+            notes
+        }
+        _ => Vec::new(),
+    }
+}
+
+// before a phase: counts down if it starts by itself, enter cancels the countdown
+// and then waits for enter to start it
+fn wait_phase(lines: &Receiver<String>, timer: &TimerConfig, kind: Phase) {
+    let name = match kind {
+        Phase::Pomodoro => "The pomodoro",
+        Phase::Break => "The pause",
+    };
+    if timer.auto_start(kind) {
+        let delay = timer.auto_start_delay.unwrap_or_default().as_secs();
+        let mut cancelled = false;
+        for left in (1..=delay).rev() {
+            println!("{} starts in {}s, press enter to wait", name, left);
+            match lines.recv_timeout(Duration::from_secs(1)) {
+                Ok(_) => { cancelled = true; break; }
+                Err(RecvTimeoutError::Timeout) => (),
+                // nobody can cancel on a closed input
+                Err(RecvTimeoutError::Disconnected) => thread::sleep(Duration::from_secs(1)),
+            }
+        }
+        if !cancelled {
+            return;
+        }
+    }
+    ask(lines, &format!("{} is ready, press enter to start it", name));
+}
+
+// the calendar that failed to be read, and why
+fn calendar_error(work_day: &PlanConfig, error: io::Error) -> String {
+    let path = work_day.calendar.as_deref().unwrap_or_else(|| Path::new(""));
+    format!("Cannot read the calendar {}: {}", path.display(), error)
+}
+
+// warns about a meeting the pomodoro would run into and offers to end the
+// pomodoro when it starts, returns the length to run
+fn fit_meeting(lines: &Receiver<String>, meetings: &[Block], length: Duration) -> Duration {
+    let now = Local::now().time();
+    let Some(meeting) = day_plan::overlap(meetings, now, length) else {
+        return length;
+    };
+    let name = meeting.name.as_deref().unwrap_or("A meeting");
+    let shortened = (meeting.start - now).to_std().unwrap_or_default().as_secs() / 60 * 60;
+    if shortened < 60 {
+        println!("{} runs until {}", name, format_time(meeting.end));
+        return length;
+    }
+    let shortened = Duration::from_secs(shortened);
+    let question = format!("{} starts at {}, run a shortened pomodoro of {}? [Y/n]",
+                           name, format_time(meeting.start), format_duration(shortened));
+    match ask(lines, &question) {
+        Some(answer) if answer.to_lowercase().starts_with('n') => length,
+        _ => shortened,
+    }
+}
+
+// waits for the planned start of a pomodoro, enter starts it right away
+fn wait_until(lines: &Receiver<String>, at: NaiveTime) {
+    let Ok(wait) = (at - Local::now().time()).to_std() else {
+        return;
+    };
+    println!("The pomodoro starts at {}, press enter to start it now", format_time(at));
+    if let Err(RecvTimeoutError::Disconnected) = lines.recv_timeout(wait) {
+        thread::sleep(wait);
     }
 }
 
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let history = History::open()?;
+    let mut tasks = match config.task {
+        Some(id) => {
+            let tasks = TaskList::open()?;
+            let name = tasks.get(id).ok_or(TaskError::NotFound(id))?.name.clone();
+            Some((tasks, id, name))
+        }
+        None => None,
+    };
+    let tag = match &tasks {
+        Some((_, _, name)) => format!("Pomodoro ({})", name),
+        None => String::from("Pomodoro"),
+    };
 
+    let lines = read_lines();
+    let counts_overtime = config.timer.overtime.unwrap_or(false);
+    // the meetings of the day, read again when it changes
+    let mut meetings: Option<(NaiveDate, Vec<Block>)> = None;
+    // planned steps wait for their start
+    let steps: Vec<(Step, Option<NaiveTime>)> = match &config.plan {
+        Some(slots) => slots.iter()
+            .filter_map(|slot| slot.step().map(|step| (step, Some(slot.start))))
+            .collect(),
+        None => config.steps().into_iter().map(|step| (step, None)).collect(),
+    };
+    // pomodoros done so far, and the length of the last one for flowtime
+    let mut pomo: u32 = 0;
+    let mut focus = Duration::ZERO;
     println!("Start focus");
-    for pomo in 0..config.num_pomodoro {
-        timer(config.dur_pomodoro, pomo, "Pomodoro");
+    for (i, (step, at)) in steps.iter().enumerate() {
+        match at {
+            Some(at) if step.kind == StepKind::Focus => wait_until(&lines, *at),
+            _ if i != 0 => wait_phase(&lines, &config.timer, step.kind.phase()),
+            _ => {}
+        }
+        let next = steps.get(i + 1).map(|(step, _)| step.kind);
+
+        if step.kind == StepKind::Focus {
+            let flowtime = config.timer.is_flowtime();
+            let length = match flowtime {
+                true => step.length,
+                false => {
+                    let today = Local::now().date_naive();
+                    if meetings.as_ref().is_none_or(|(day, _)| *day != today) {
+                        let read = config.work_day.meetings(today).unwrap_or_else(|e| {
+                            eprintln!("{}", calendar_error(&config.work_day, e));
+                            Vec::new()
+                        });
+                        meetings = Some((today, read));
+                    }
+                    let today_meetings = meetings.as_ref().map_or(&[][..], |(_, read)| read);
+                    fit_meeting(&lines, today_meetings, step.length)
+                }
+            };
+            let intention = match config.prompts.intention {
+                true => ask(&lines, "What will you do?"),
+                false => None,
+            };
+            let title = format!("{} n° {}", tag, pomo);
+            let mut pomodoro = match flowtime {
+                true => run_focus(&lines, &history, &title),
+                false => run_phase(&lines, &history, Phase::Pomodoro, length,
+                                   &title, &[], counts_overtime),
+            };
+            let completed = pomodoro.outcome == Outcome::Completed;
+            focus = pomodoro.duration();
+            pomodoro.task = tasks.as_ref().map(|(_, _, name)| name.clone());
+            pomodoro.intention = intention;
+            if config.prompts.reflection && completed {
+                reflect(&lines, &mut pomodoro);
+            }
+            record(&history, pomodoro);
+            if let Some((tasks, id, _)) = tasks.as_mut().filter(|_| completed) {
+                if let Err(e) = tasks.add_pomodoro(*id) {
+                    eprintln!("Cannot save the task: {}", e);
+                }
+            }
+            pomo += 1;
 
-        println!("Great job take a break!!");
+            if next.is_some_and(|kind| kind != StepKind::Focus) {
+                println!("Great job take a break!!");
+            }
+            continue;
+        }
 
-        timer(config.dur_pause, pomo, "Pause");
+        let length = match config.timer.is_flowtime() {
+            true => config.timer.flowtime.break_after(focus),
+            false => step.length,
+        };
+        let pause_tag = match step.kind {
+            StepKind::LongBreak => "Long pause",
+            _ => "Pause",
+        };
+        let pause = run_phase(&lines, &history, Phase::Break, length,
+                              &format!("{} n° {}", pause_tag, pomo.saturating_sub(1)),
+                              &inbox_notes(), counts_overtime);
+        record(&history, pause);
 
-        if pomo != 0 {
+        if pomo > 1 {
             println!("Go back to work!!");
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_arg() {
+        let columns = columns_arg(vec!["task=Description", "start = Begin"].into_iter()).unwrap();
+        assert_eq!(columns.task, "Description");
+        assert_eq!(columns.start, "Begin");
+        assert!(columns_arg(vec!["task"].into_iter()).is_err());
+        assert!(columns_arg(vec!["project=Project"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_run_phase_records_reset() {
+        let dir = std::env::temp_dir().join("pomodoro-cmd-reset-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let history = History::new(dir.join("history.jsonl"));
+        let (sender, lines) = mpsc::channel();
+        sender.send(String::from("r")).unwrap();
+        sender.send(String::from("s")).unwrap();
+        let phase = run_phase(&lines, &history, Phase::Pomodoro, Duration::from_secs(60),
+                              "test", &[], false);
+        assert_eq!(phase.outcome, Outcome::Skipped);
+        let records = history.load().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].outcome, Outcome::Reset);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_number_arg() {
+        assert_eq!(number_arg("pomo", "4").unwrap(), 4);
+        assert!(matches!(number_arg("pomo", "0"), Err(ConfigError::ZeroNumber { arg: "pomo" })));
+        assert!(matches!(number_arg("pomo", "four"),
+                         Err(ConfigError::InvalidNumber { arg: "pomo", .. })));
+    }
+
+    #[test]
+    fn test_date_arg() {
+        assert_eq!(date_arg("since", "2024-03-01").unwrap(),
+                   NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert!(matches!(date_arg("since", "yesterday"),
+                         Err(ConfigError::InvalidDate { arg: "since", .. })));
+    }
+
+    #[test]
+    fn test_duration_arg() {
+        assert_eq!(duration_arg("dur", "25").unwrap(), Duration::from_secs(25 * 60));
+        assert_eq!(duration_arg("dur", "1h30m").unwrap(), Duration::from_secs(90 * 60));
+        assert!(matches!(duration_arg("pause", "0m"),
+                         Err(ConfigError::ZeroDuration { arg: "pause" })));
+        assert!(matches!(duration_arg("dur", "5x"),
+                         Err(ConfigError::InvalidDuration { arg: "dur", .. })));
+    }
+}
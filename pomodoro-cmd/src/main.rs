@@ -1,20 +1,44 @@
 use std::process;
-use pomodoro::Config;
+use pomodoro::Command;
+use pomodoro_core::duration::format_duration;
+use pomodoro_core::schedule::StepKind;
 
 fn main() {
     let app_name = "pomodoro";
-    let config = Config::new().unwrap_or_else(|err| {
+    let command = Command::new().unwrap_or_else(|err| {
         eprintln!("Error during argument parsing: {}", err);
         eprintln!("try {} --help", app_name);
         process::exit(1);
     });
 
-    println!("N° pomodoro: {}, during: {} minutes, with pauses of: {} minutes",
-             config.num_pomodoro,
-             config.dur_pomodoro,
-             config.dur_pause);
+    let result = match command {
+        Command::Run(config) if config.timer.schedule.is_some() => {
+            let steps = config.steps();
+            let focus = steps.iter().filter(|step| step.kind == StepKind::Focus).count();
+            println!("N° pomodoro: {}, in {} steps", focus, steps.len());
+            pomodoro::run(config)
+        }
+        Command::Run(config) if config.timer.is_flowtime() => {
+            println!("N° pomodoro: {}, in flowtime", config.num_pomodoro);
+            pomodoro::run(config)
+        }
+        Command::Run(config) => {
+            println!("N° pomodoro: {}, during: {}, with pauses of: {}",
+                     config.num_pomodoro,
+                     format_duration(config.dur_pomodoro),
+                     format_duration(config.dur_pause));
+            pomodoro::run(config)
+        }
+        Command::Stats(options) => pomodoro::stats::run(options),
+        Command::Export(options) => pomodoro::export::run(options),
+        Command::Import(options) => pomodoro::import::run(options),
+        Command::Task(command) => pomodoro::task::run(command),
+        Command::Mark(options) => pomodoro::mark::run(options),
+        Command::Plan(options) => pomodoro::plan::run(options),
+        Command::Inbox(command) => pomodoro::inbox::run(command),
+    };
 
-    if let Err(e) = pomodoro::run(config) {
+    if let Err(e) = result {
         eprintln!("Application error: {}", e);
         process::exit(1);
     }
@@ -0,0 +1,21 @@
+use std::error::Error;
+use pomodoro_core::history::{History, Interruption, InterruptionKind, Phase};
+
+pub struct MarkOptions {
+    pub kind: InterruptionKind,
+    pub note: Option<String>,
+}
+
+pub fn run(options: MarkOptions) -> Result<(), Box<dyn Error>> {
+    let history = History::open()?;
+    let running = history.running()?
+        .filter(|running| running.kind == Phase::Pomodoro)
+        .ok_or("no running pomodoro")?;
+    history.mark(&Interruption::now(options.kind, options.note))?;
+
+    let marks = history.marks_since(running.start)?;
+    let symbols: String = marks.iter().map(|mark| mark.kind.symbol()).collect();
+    println!("Interruptions of the pomodoro started at {}: {}",
+             running.start.format("%H:%M"), symbols);
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::time::Duration;
+use chrono::{Local, NaiveTime};
+use pomodoro_core::duration::format_duration;
+use pomodoro_core::plan::{self, format_time, Block, SlotKind};
+use pomodoro_core::schedule::StepKind;
+use crate::Config;
+
+pub struct PlanOptions {
+    /// Timer settings, for the length of the pomodoros and breaks.
+    pub config: Config,
+    pub from: NaiveTime,
+    pub until: NaiveTime,
+    /// Lunch and fixed blocks of the config file, the meetings of the
+    /// calendar are added to them.
+    pub busy: Vec<Block>,
+    /// Runs the timer, each pomodoro starting at its planned time.
+    pub start: bool,
+}
+
+pub fn run(options: PlanOptions) -> Result<(), Box<dyn Error>> {
+    let PlanOptions { mut config, from, until, mut busy, start } = options;
+    // planned pomodoros have a fixed length
+    config.timer.mode = None;
+    busy.extend(config.work_day.meetings(Local::now().date_naive())
+        .map_err(|e| crate::calendar_error(&config.work_day, e))?);
+    let steps = plan::day_steps(&config.timer, config.dur_pomodoro, config.dur_pause);
+    let slots = plan::plan(from, until, &steps, &busy);
+
+    let mut pomodoros = 0;
+    let mut focus = Duration::ZERO;
+    for slot in &slots {
+        let what = match &slot.kind {
+            SlotKind::Step(StepKind::Focus) => {
+                focus += slot.step().map_or(Duration::ZERO, |step| step.length);
+                pomodoros += 1;
+                // numbered like the pomodoros of the timer
+                format!("Pomodoro n° {}", pomodoros - 1)
+            }
+            SlotKind::Step(StepKind::Break) => String::from("Pause"),
+            SlotKind::Step(StepKind::LongBreak) => String::from("Long pause"),
+            SlotKind::Busy(name) => name.clone().unwrap_or_else(|| String::from("Busy")),
+        };
+        println!("{}-{} {}", format_time(slot.start), format_time(slot.end), what);
+    }
+    println!("{} pomodoros, {} of focus", pomodoros, format_duration(focus));
+
+    if start && pomodoros > 0 {
+        config.plan = Some(slots);
+        crate::run(config)?;
+    }
+    Ok(())
+}
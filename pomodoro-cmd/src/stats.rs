@@ -0,0 +1,86 @@
+use std::error::Error;
+use pomodoro_core::calendar::Calendar;
+use pomodoro_core::config::Config;
+use pomodoro_core::duration::format_duration;
+use pomodoro_core::estimates::{EstimateReport, BUCKETS, ROLLING_TASKS};
+use pomodoro_core::heatmap::{Heatmap, ASCII_SHADES, SHADES, YEAR_WEEKS};
+use pomodoro_core::history::History;
+use pomodoro_core::stats::{self, Summary};
+use pomodoro_core::tasks::TaskList;
+
+pub struct StatsOptions {
+    pub heatmap: bool,
+    pub ascii: bool,
+    pub estimates: bool,
+}
+
+pub fn run(options: StatsOptions) -> Result<(), Box<dyn Error>> {
+    let calendar = Calendar::from_config(&Config::load()?.stats)?;
+    let records = History::open()?.load()?;
+    let summary = Summary::new(&records, &calendar);
+    let today = calendar.today();
+
+    let rate = summary.completion_rate()
+        .map_or_else(|| String::from("-"), |rate| format!("{:.0}%", rate * 100.0));
+    println!("Pomodoros: {} completed, {} interrupted, {} skipped, {} voided, completion rate {}",
+             summary.completed, summary.interrupted, summary.skipped, summary.voided, rate);
+    println!("Focus time: {} in {} days, {} of overtime",
+             format_duration(summary.focus), summary.active_days,
+             format_duration(summary.overtime));
+    println!("Daily average: {:.1} pomodoros, {} of focus",
+             summary.average_completed(), format_duration(summary.average_focus()));
+    let week = stats::completed_by_week(&records, &calendar)
+        .get(&calendar.week_of(today)).copied().unwrap_or(0);
+    let month = stats::completed_by_month(&records, &calendar)
+        .get(&calendar.month_of(today)).copied().unwrap_or(0);
+    println!("Today: {} pomodoros, this week: {}, this month: {}",
+             stats::completed_on(&records, &calendar, today).count(), week, month);
+    println!("Interruptions: {} internal ('), {} external (-)",
+             summary.internal_interruptions, summary.external_interruptions);
+    if let Some(rating) = summary.focus_rating {
+        println!("Focus rating: {:.1}/5", rating);
+    }
+    for record in stats::completed_on(&records, &calendar, today) {
+        if let Some(line) = record.notes() {
+            println!("  {} {}", record.start.format("%H:%M"), line);
+        }
+    }
+
+    if options.heatmap {
+        let shades = if options.ascii || !pomodoro_core::supports_unicode() {
+            &ASCII_SHADES
+        } else {
+            &SHADES
+        };
+        println!();
+        for line in Heatmap::new(&records, &calendar, today, YEAR_WEEKS).lines(shades) {
+            println!("{}", line);
+        }
+    }
+
+    if options.estimates {
+        println!();
+        print_estimates(&EstimateReport::new(TaskList::open()?.tasks()), options.ascii);
+    }
+
+    Ok(())
+}
+
+fn print_estimates(report: &EstimateReport, ascii: bool) {
+    let Some(accuracy) = report.accuracy() else {
+        println!("No done tasks with an estimate");
+        return;
+    };
+    for estimate in &report.estimates {
+        println!("{:>3}/{:<3} {:<20} {}",
+                 estimate.actual, estimate.estimate, estimate.verdict(), estimate.name);
+    }
+    println!("Accuracy of the last {} tasks: {:.0}%",
+             ROLLING_TASKS.min(report.estimates.len()), accuracy * 100.0);
+
+    // actual minus estimated pomodoros
+    let bar = if ascii { "#" } else { "█" };
+    for (label, count) in BUCKETS.iter().zip(report.histogram()) {
+        println!("{:>3} {} {}", label, bar.repeat(count as usize), count);
+    }
+}
@@ -0,0 +1,48 @@
+use std::error::Error;
+use pomodoro_core::tasks::{Task, TaskList};
+
+pub enum TaskCommand {
+    Add { name: String, estimate: Option<u32> },
+    List { all: bool },
+    Done { id: u32 },
+}
+
+pub fn run(command: TaskCommand) -> Result<(), Box<dyn Error>> {
+    let mut tasks = TaskList::open()?;
+    match command {
+        TaskCommand::Add { name, estimate } => {
+            let task = tasks.add(&name, estimate)?;
+            println!("Added task {}: {}", task.id, task.name);
+        }
+        TaskCommand::List { all } => {
+            let shown: Vec<&Task> = tasks.tasks().iter()
+                .filter(|task| all || !task.is_done())
+                .collect();
+            if shown.is_empty() {
+                println!("No tasks, add one with `pomodoro task add NAME`");
+            }
+            for task in shown {
+                println!("{:>3} [{}] {:>5} {}",
+                         task.id,
+                         if task.is_done() { "x" } else { " " },
+                         pomodoros(task),
+                         task.name);
+            }
+        }
+        TaskCommand::Done { id } => {
+            tasks.mark_done(id)?;
+            if let Some(task) = tasks.get(id) {
+                println!("Task {} done: {} ({} pomodoros)", task.id, task.name, pomodoros(task));
+            }
+        }
+    }
+    Ok(())
+}
+
+// actual pomodoros over the estimate, "2/3" or just "2"
+fn pomodoros(task: &Task) -> String {
+    match task.estimate {
+        Some(estimate) => format!("{}/{}", task.actual, estimate),
+        None => task.actual.to_string(),
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+use crate::config::StatsConfig;
+
+/// Splits time in days, weeks and months for the statistics.
+///
+/// Days start at `day_starts_at` o'clock on the wall clock of the configured
+/// timezone (the system one by default), so a pomodoro at 00:30 still counts
+/// for the day before when days start at 4:00. Working on the wall clock keeps
+/// the boundary at the same hour across DST changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Calendar {
+    day_starts_at: u32,
+    timezone: Option<Tz>,
+}
+
+/// Errors returned by [`Calendar::from_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalendarError {
+    InvalidHour(u32),
+    UnknownTimezone(String),
+}
+
+impl fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalendarError::InvalidHour(hour) => {
+                write!(f, "day_starts_at must be between 0 and 23, not {}", hour)
+            }
+            CalendarError::UnknownTimezone(name) => write!(f, "unknown timezone '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {}
+
+impl Calendar {
+    pub fn new(day_starts_at: u32, timezone: Option<Tz>) -> Result<Self, CalendarError> {
+        if day_starts_at > 23 {
+            return Err(CalendarError::InvalidHour(day_starts_at));
+        }
+        Ok(Calendar {
+            day_starts_at,
+            timezone,
+        })
+    }
+
+    pub fn from_config(config: &StatsConfig) -> Result<Self, CalendarError> {
+        let timezone = match &config.timezone {
+            Some(name) => Some(
+                name.parse::<Tz>()
+                    .map_err(|_| CalendarError::UnknownTimezone(name.clone()))?,
+            ),
+            None => None,
+        };
+        Calendar::new(config.day_starts_at.unwrap_or(0), timezone)
+    }
+
+    /// Day `time` belongs to.
+    pub fn day_of<T: TimeZone>(&self, time: &DateTime<T>) -> NaiveDate {
+        let shifted = self.wall_clock(time) - chrono::Duration::hours(self.day_starts_at.into());
+        shifted.date()
+    }
+
+    /// Hour of the wall clock, not moved by the day start.
+    pub fn hour_of<T: TimeZone>(&self, time: &DateTime<T>) -> u32 {
+        self.wall_clock(time).hour()
+    }
+
+    /// Monday of the week `day` is in.
+    pub fn week_of(&self, day: NaiveDate) -> NaiveDate {
+        day - Days::new(day.weekday().num_days_from_monday().into())
+    }
+
+    /// First day of the month `day` is in.
+    pub fn month_of(&self, day: NaiveDate) -> NaiveDate {
+        day.with_day(1).expect("every month has a first day")
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.day_of(&Local::now())
+    }
+
+    fn wall_clock<T: TimeZone>(&self, time: &DateTime<T>) -> NaiveDateTime {
+        match self.timezone {
+            Some(tz) => time.with_timezone(&tz).naive_local(),
+            None => time.with_timezone(&Local).naive_local(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rome() -> Calendar {
+        Calendar::new(4, Some(chrono_tz::Europe::Rome)).unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_day_start() {
+        let calendar = rome();
+        let tz = chrono_tz::Europe::Rome;
+        let late = tz.with_ymd_and_hms(2024, 3, 5, 0, 30, 0).unwrap();
+        assert_eq!(calendar.day_of(&late), date(2024, 3, 4));
+        let morning = tz.with_ymd_and_hms(2024, 3, 5, 4, 0, 0).unwrap();
+        assert_eq!(calendar.day_of(&morning), date(2024, 3, 5));
+        assert_eq!(calendar.hour_of(&late), 0);
+    }
+
+    #[test]
+    fn test_dst() {
+        let calendar = rome();
+        // clocks go from 2:00 to 3:00 on 2024-03-31 and from 3:00 to 2:00 on
+        // 2024-10-27, the day still changes at 4:00 on the wall clock
+        let spring = chrono::Utc.with_ymd_and_hms(2024, 3, 31, 1, 59, 0).unwrap();
+        assert_eq!(calendar.day_of(&spring), date(2024, 3, 30));
+        let spring = chrono::Utc.with_ymd_and_hms(2024, 3, 31, 2, 0, 0).unwrap();
+        assert_eq!(calendar.day_of(&spring), date(2024, 3, 31));
+        let autumn = chrono::Utc
+            .with_ymd_and_hms(2024, 10, 27, 2, 59, 0)
+            .unwrap();
+        assert_eq!(calendar.day_of(&autumn), date(2024, 10, 26));
+        let autumn = chrono::Utc.with_ymd_and_hms(2024, 10, 27, 3, 0, 0).unwrap();
+        assert_eq!(calendar.day_of(&autumn), date(2024, 10, 27));
+    }
+
+    #[test]
+    fn test_week_and_month() {
+        let calendar = rome();
+        assert_eq!(calendar.week_of(date(2024, 3, 3)), date(2024, 2, 26));
+        assert_eq!(calendar.week_of(date(2024, 3, 4)), date(2024, 3, 4));
+        assert_eq!(calendar.month_of(date(2024, 3, 31)), date(2024, 3, 1));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = StatsConfig {
+            day_starts_at: Some(24),
+            timezone: None,
+        };
+        assert_eq!(
+            Calendar::from_config(&config),
+            Err(CalendarError::InvalidHour(24))
+        );
+        let config = StatsConfig {
+            day_starts_at: None,
+            timezone: Some("Mars/Olympus".to_string()),
+        };
+        assert!(Calendar::from_config(&config).is_err());
+    }
+}
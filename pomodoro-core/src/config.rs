@@ -0,0 +1,432 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::flowtime::FlowtimeConfig;
+use crate::history::Phase;
+use crate::plan::PlanConfig;
+use crate::reminder::ReminderConfig;
+use crate::schedule::Schedule;
+
+// environment variable used to override the config file location
+pub const CONFIG_ENV: &str = "POMODORO_CONFIG";
+
+/// Configuration shared by every pomodoro frontend.
+///
+/// It lives in `$XDG_CONFIG_HOME/pomodoro/config.toml` and every key is
+/// optional, a missing file is the same as an empty one.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Timer settings, unset values keep the frontend defaults.
+    pub timer: TimerConfig,
+    /// Named timer settings, picked instead of `timer`.
+    pub profiles: BTreeMap<String, TimerConfig>,
+    /// How the statistics split time in days.
+    pub stats: StatsConfig,
+    /// Questions asked around each pomodoro.
+    pub prompts: PromptsConfig,
+    /// Name of the theme to use, either a built-in one or one of `themes`.
+    pub theme: Option<String>,
+    /// User defined themes.
+    pub themes: BTreeMap<String, ThemeConfig>,
+    /// Work day of `pomodoro plan`.
+    #[serde(skip_serializing_if = "PlanConfig::is_unset")]
+    pub plan: PlanConfig,
+    /// Reminders to start a pomodoro during the work hours.
+    #[serde(skip_serializing_if = "ReminderConfig::is_unset")]
+    pub reminder: ReminderConfig,
+}
+
+/// Length and number of the pomodoros, in `[timer]` or in a profile.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pomodoros: Option<u32>,
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pomodoro_duration: Option<Duration>,
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub break_duration: Option<Duration>,
+    /// Pause after which a pomodoro is voided, never if unset.
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub void_after: Option<Duration>,
+    /// Break taken every `long_break_every` pomodoros instead of the short one.
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub long_break_duration: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_break_every: Option<u32>,
+    /// Start the break as soon as the pomodoro ends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_start_breaks: Option<bool>,
+    /// Start the pomodoro as soon as the break ends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_start_pomodoros: Option<bool>,
+    /// Countdown before a phase starts by itself, it can be cancelled.
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub auto_start_delay: Option<Duration>,
+    /// Keep counting past the end of a phase, until the user moves on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overtime: Option<bool>,
+    /// Fixed length pomodoros or flowtime, pomodoros if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<TimerMode>,
+    /// Break rules of the flowtime mode.
+    #[serde(skip_serializing_if = "FlowtimeConfig::is_unset")]
+    pub flowtime: FlowtimeConfig,
+    /// Custom sequence of phases, it replaces the pomodoros and the mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
+}
+
+/// How the length of the focus and of the break is decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerMode {
+    /// Fixed length pomodoros and breaks.
+    Pomodoro,
+    /// Focus until the user stops, with a break proportional to the focus.
+    Flowtime,
+}
+
+impl TimerConfig {
+    /// Values of `self`, the unset ones taken from `base`.
+    pub fn or(&self, base: &TimerConfig) -> TimerConfig {
+        TimerConfig {
+            pomodoros: self.pomodoros.or(base.pomodoros),
+            pomodoro_duration: self.pomodoro_duration.or(base.pomodoro_duration),
+            break_duration: self.break_duration.or(base.break_duration),
+            void_after: self.void_after.or(base.void_after),
+            long_break_duration: self.long_break_duration.or(base.long_break_duration),
+            long_break_every: self.long_break_every.or(base.long_break_every),
+            auto_start_breaks: self.auto_start_breaks.or(base.auto_start_breaks),
+            auto_start_pomodoros: self.auto_start_pomodoros.or(base.auto_start_pomodoros),
+            auto_start_delay: self.auto_start_delay.or(base.auto_start_delay),
+            overtime: self.overtime.or(base.overtime),
+            mode: self.mode.or(base.mode),
+            flowtime: self.flowtime.or(&base.flowtime),
+            schedule: self.schedule.clone().or_else(|| base.schedule.clone()),
+        }
+    }
+
+    /// Whether the focus counts up, never with a schedule.
+    pub fn is_flowtime(&self) -> bool {
+        self.mode == Some(TimerMode::Flowtime) && self.schedule.is_none()
+    }
+
+    /// Whether a `kind` phase starts by itself, in every frontend it waits
+    /// to be started if unset.
+    pub fn auto_start(&self, kind: Phase) -> bool {
+        match kind {
+            Phase::Pomodoro => self.auto_start_pomodoros,
+            Phase::Break => self.auto_start_breaks,
+        }
+        .unwrap_or(false)
+    }
+
+    /// Length of the break after the `completed`-th pomodoro, `short` unless
+    /// a long break is due.
+    pub fn break_after(&self, completed: u32, short: Duration) -> Duration {
+        match (self.long_break_duration, self.long_break_every) {
+            (Some(long), Some(every))
+                if every > 0 && completed > 0 && completed.is_multiple_of(every) =>
+            {
+                long
+            }
+            _ => short,
+        }
+    }
+}
+
+/// Day boundary of the statistics.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Hour the day starts at, 0 (midnight) if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_starts_at: Option<u32>,
+    /// IANA name of the timezone, like `Europe/Rome`, the system one if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+/// Questions asked before and after each pomodoro, none by default.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptsConfig {
+    /// Ask what the pomodoro is for before starting it.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub intention: bool,
+    /// Ask what got done and how focused it was at the end.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reflection: bool,
+}
+
+/// A user theme, every slot holds a color name (`"red"`, `"#ff8800"`, ...).
+///
+/// Slots left empty are taken from the `base` theme.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub active_edit: Option<String>,
+    pub idle: Option<String>,
+    pub running: Option<String>,
+    pub finished: Option<String>,
+    pub overtime: Option<String>,
+    pub border: Option<String>,
+    pub help: Option<String>,
+}
+
+/// Errors returned while reading or writing the config file.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// The file to save into isn't valid toml anymore.
+    Edit(PathBuf, Box<toml_edit::TomlError>),
+    Serialize(toml::ser::Error),
+    /// There is no config directory on this system.
+    NoPath,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "cannot access {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Edit(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Serialize(e) => write!(f, "cannot write config: {}", e),
+            ConfigError::NoPath => write!(f, "no config directory, set {}", CONFIG_ENV),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Path of the config file, `POMODORO_CONFIG` wins over the default location.
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    dirs::config_dir().map(|dir| dir.join("pomodoro").join("config.toml"))
+}
+
+impl Config {
+    /// Loads the config from the default location.
+    pub fn load() -> Result<Config, ConfigError> {
+        match config_path() {
+            Some(path) => Config::load_from(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Loads the config from `path`, a missing file gives the default config.
+    pub fn load_from(path: &Path) -> Result<Config, ConfigError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        toml::from_str(&content).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    /// Timer settings of the profile `name` over the `[timer]` ones, `None`
+    /// if there is no such profile.
+    pub fn profile(&self, name: &str) -> Option<TimerConfig> {
+        self.profiles
+            .get(name)
+            .map(|profile| profile.or(&self.timer))
+    }
+
+    /// Writes the config to the default location.
+    pub fn save(&self) -> Result<(), ConfigError> {
+        self.save_to(&config_path().ok_or(ConfigError::NoPath)?)
+    }
+
+    /// Writes the config to `path`, creating its directory if needed. An
+    /// existing file is edited in place: its comments and the order of its
+    /// keys are kept.
+    pub fn save_to(&self, path: &Path) -> Result<(), ConfigError> {
+        let content = toml::to_string_pretty(self).map_err(ConfigError::Serialize)?;
+        let saved: DocumentMut = content.parse().expect("always valid toml");
+        let mut doc = match fs::read_to_string(path) {
+            Ok(old) => old
+                .parse::<DocumentMut>()
+                .map_err(|e| ConfigError::Edit(path.to_path_buf(), Box::new(e)))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => DocumentMut::new(),
+            Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+        };
+        merge(doc.as_table_mut(), saved.as_table());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| ConfigError::Io(dir.to_path_buf(), e))?;
+        }
+        fs::write(path, doc.to_string()).map_err(|e| ConfigError::Io(path.to_path_buf(), e))
+    }
+}
+
+// copies the keys of `saved` into `doc`, only the changed values are replaced
+fn merge(doc: &mut Table, saved: &Table) {
+    let unset: Vec<String> = doc
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !saved.contains_key(key))
+        .collect();
+    for key in unset {
+        doc.remove(&key);
+    }
+    for (key, item) in saved.iter() {
+        match (doc.get_mut(key), item) {
+            (Some(Item::Table(old)), Item::Table(new)) => merge(old, new),
+            (Some(Item::Value(old)), Item::Value(new)) => {
+                let mut bare = old.clone();
+                bare.decor_mut().clear();
+                if bare.to_string() != new.to_string() {
+                    let decor = old.decor().clone();
+                    *old = new.clone();
+                    *old.decor_mut() = decor;
+                }
+            }
+            _ => {
+                doc.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_user_theme() {
+        let config: Config = toml::from_str(
+            r##"
+            theme = "mine"
+
+            [themes.mine]
+            base = "light"
+            running = "#ff0000"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.theme.as_deref(), Some("mine"));
+        let mine = &config.themes["mine"];
+        assert_eq!(mine.base.as_deref(), Some("light"));
+        assert_eq!(mine.running.as_deref(), Some("#ff0000"));
+        assert_eq!(mine.idle, None);
+    }
+
+    #[test]
+    fn test_timer_round_trip() {
+        let mut config = Config::default();
+        config.timer.pomodoros = Some(4);
+        config.timer.pomodoro_duration = Some(Duration::from_secs(25 * 60));
+        config.plan.from = Some(crate::plan::parse_time("9:00").unwrap());
+        config.plan.lunch = Some("12:30-13:30".parse().unwrap());
+        config.reminder.every = Some(Duration::from_secs(20 * 60));
+        let path = std::env::temp_dir().join("pomodoro-core-round-trip.toml");
+        config.save_to(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("pomodoro_duration = \"25m\""));
+        assert!(content.contains("from = \"09:00\""));
+        assert!(content.contains("every = \"20m\""));
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config: Config = toml::from_str(
+            r#"
+            [timer]
+            pomodoros = 4
+            void_after = "10m"
+
+            [profiles.deep-work]
+            pomodoros = 3
+            pomodoro_duration = "50m"
+            break_duration = "10m"
+            long_break_duration = "30m"
+            long_break_every = 2
+            auto_start_breaks = true
+
+            [profiles.flow]
+            mode = "flowtime"
+
+            [profiles.flow.flowtime]
+            max_break = "15m"
+
+            [profiles.sprint]
+            schedule = "(25f 5b)x2 50 focus"
+            "#,
+        )
+        .unwrap();
+        assert!(config.profile("classic").is_none());
+        let deep = config.profile("deep-work").unwrap();
+        assert_eq!(deep.pomodoros, Some(3));
+        assert_eq!(deep.void_after, Some(Duration::from_secs(10 * 60)));
+        assert!(deep.auto_start(Phase::Break));
+        assert!(!deep.auto_start(Phase::Pomodoro));
+
+        let short = Duration::from_secs(10 * 60);
+        assert_eq!(deep.break_after(1, short), short);
+        assert_eq!(deep.break_after(2, short), Duration::from_secs(30 * 60));
+        assert_eq!(config.timer.break_after(2, short), short);
+
+        let flow = config.profile("flow").unwrap();
+        assert!(flow.is_flowtime() && !deep.is_flowtime());
+        assert_eq!(
+            flow.flowtime.break_after(Duration::from_secs(100 * 60)),
+            Duration::from_secs(15 * 60)
+        );
+        let sprint = config.profile("sprint").unwrap();
+        assert_eq!(sprint.schedule.unwrap().steps().len(), 5);
+        assert!(toml::from_str::<Config>("[timer]\nschedule = \"25 nap\"").is_err());
+    }
+
+    #[test]
+    fn test_save_keeps_comments() {
+        let path = std::env::temp_dir().join("pomodoro-core-save-config.toml");
+        let text = "# my timer\n[timer]\n# the classic length\npomodoro_duration = \"25m\" # or 50m\npomodoros = 4\n";
+        fs::write(&path, text).unwrap();
+        let mut config = Config::load_from(&path).unwrap();
+        config.timer.pomodoro_duration = Some(Duration::from_secs(50 * 60));
+        config.timer.pomodoros = None;
+        config.save_to(&path).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("# my timer\n[timer]\n# the classic length\n"));
+        assert!(saved.contains("pomodoro_duration = \"50m\" # or 50m"));
+        assert!(!saved.contains("pomodoros"));
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_is_default() {
+        let path = std::env::temp_dir().join("pomodoro-core-missing-config.toml");
+        assert_eq!(Config::load_from(&path).unwrap(), Config::default());
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned by [`parse_duration`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DurationError {
+    /// Nothing to parse.
+    Empty,
+    /// No number at all, like `soon`.
+    NoNumber,
+    /// A unit written before its number, like `m5`.
+    UnitFirst(char),
+    /// A unit without the number in front of it, like the `m` in `1hm`.
+    MissingNumber(char),
+    /// A number without the unit after it, like the `30` in `1h30`.
+    MissingUnit(u64),
+    /// A unit other than `h`, `m` and `s`.
+    UnknownUnit(char),
+    /// The duration doesn't fit in a [`Duration`].
+    TooLong,
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DurationError::Empty => write!(f, "empty duration"),
+            DurationError::NoNumber => write!(f, "no number in the duration, like 25m"),
+            DurationError::UnitFirst(unit) => {
+                write!(f, "'{}' goes after its number, like 5{}", unit, unit)
+            }
+            DurationError::MissingNumber(unit) => write!(f, "missing number before '{}'", unit),
+            DurationError::MissingUnit(n) => write!(f, "missing unit after {}, use h, m or s", n),
+            DurationError::UnknownUnit(unit) => write!(f, "unknown unit '{}', use h, m or s", unit),
+            DurationError::TooLong => write!(f, "duration too long"),
+        }
+    }
+}
+
+impl std::error::Error for DurationError {}
+
+/// Parses a human written duration.
+///
+/// A plain number is a number of minutes (`25`), otherwise every number needs
+/// a unit: `25m`, `90s`, `1h10m`, `1h 10m 30s`.
+pub fn parse_duration(text: &str) -> Result<Duration, DurationError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(DurationError::Empty);
+    }
+    if let Ok(minutes) = text.parse::<u64>() {
+        return minutes_to_duration(minutes);
+    }
+
+    if !text.chars().any(|c| c.is_ascii_digit()) {
+        return Err(DurationError::NoNumber);
+    }
+
+    let mut total = Duration::ZERO;
+    let mut number: Option<u64> = None;
+    for (i, c) in text.chars().enumerate() {
+        if let Some(digit) = c.to_digit(10) {
+            let n = number
+                .unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|n| n.checked_add(u64::from(digit)))
+                .ok_or(DurationError::TooLong)?;
+            number = Some(n);
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+        if !matches!(c.to_ascii_lowercase(), 'h' | 'm' | 's') {
+            return Err(DurationError::UnknownUnit(c));
+        }
+        let n = match number.take() {
+            Some(n) => n,
+            None if i == 0 => return Err(DurationError::UnitFirst(c)),
+            None => return Err(DurationError::MissingNumber(c)),
+        };
+        let part = match c.to_ascii_lowercase() {
+            'h' => n
+                .checked_mul(60)
+                .ok_or(DurationError::TooLong)
+                .and_then(minutes_to_duration)?,
+            'm' => minutes_to_duration(n)?,
+            _ => Duration::from_secs(n),
+        };
+        total = total.checked_add(part).ok_or(DurationError::TooLong)?;
+    }
+    match number {
+        Some(n) => Err(DurationError::MissingUnit(n)),
+        None => Ok(total),
+    }
+}
+
+/// Formats a duration the way [`parse_duration`] reads it: `1h30m`, `25m`, `45s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let mut text = String::new();
+    if hours > 0 {
+        text.push_str(&format!("{}h", hours));
+    }
+    if minutes > 0 {
+        text.push_str(&format!("{}m", minutes));
+    }
+    if seconds > 0 || text.is_empty() {
+        text.push_str(&format!("{}s", seconds));
+    }
+    text
+}
+
+/// Serde helpers to store an optional duration as `"25m"` in config files.
+///
+/// Use it with `#[serde(default, with = "crate::duration::opt_text")]`.
+pub mod opt_text {
+    use super::{format_duration, parse_duration};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => s.serialize_str(&format_duration(*duration)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        match Option::<String>::deserialize(d)? {
+            Some(text) => parse_duration(&text).map(Some).map_err(de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+fn minutes_to_duration(minutes: u64) -> Result<Duration, DurationError> {
+    minutes
+        .checked_mul(60)
+        .map(Duration::from_secs)
+        .ok_or(DurationError::TooLong)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mins(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    #[test]
+    fn test_plain_number_is_minutes() {
+        assert_eq!(parse_duration("25"), Ok(mins(25)));
+        assert_eq!(parse_duration(" 5 "), Ok(mins(5)));
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(parse_duration("25m"), Ok(mins(25)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h10m"), Ok(mins(70)));
+        assert_eq!(
+            parse_duration("1H 10M 30S"),
+            Ok(Duration::from_secs(70 * 60 + 30))
+        );
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        for text in ["1h30m", "25m", "45s", "2h5s", "0s"] {
+            assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_duration(""), Err(DurationError::Empty));
+        assert_eq!(parse_duration("h30m"), Err(DurationError::UnitFirst('h')));
+        assert_eq!(parse_duration("m5"), Err(DurationError::UnitFirst('m')));
+        assert_eq!(parse_duration("soon"), Err(DurationError::NoNumber));
+        assert_eq!(parse_duration("x5"), Err(DurationError::UnknownUnit('x')));
+        assert_eq!(
+            parse_duration("1hm"),
+            Err(DurationError::MissingNumber('m'))
+        );
+        assert_eq!(parse_duration("1h30"), Err(DurationError::MissingUnit(30)));
+        assert_eq!(parse_duration("10x"), Err(DurationError::UnknownUnit('x')));
+        assert_eq!(
+            parse_duration("99999999999999999999m"),
+            Err(DurationError::TooLong)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::tasks::Task;
+
+/// Done tasks in the rolling accuracy.
+pub const ROLLING_TASKS: usize = 10;
+
+/// Labels of the histogram buckets, actual minus estimated pomodoros.
+pub const BUCKETS: [&str; 7] = ["-3", "-2", "-1", "0", "+1", "+2", "+3"];
+
+/// Estimated and actual pomodoros of a done task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Estimate {
+    pub name: String,
+    pub estimate: u32,
+    pub actual: u32,
+}
+
+impl Estimate {
+    /// Pomodoros over the estimate, negative when the task took less.
+    pub fn error(&self) -> i64 {
+        i64::from(self.actual) - i64::from(self.estimate)
+    }
+
+    /// How far off the estimate was, like `underestimated by 2`.
+    pub fn verdict(&self) -> String {
+        match self.error() {
+            0 => String::from("on target"),
+            e if e > 0 => format!("underestimated by {}", e),
+            e => format!("overestimated by {}", -e),
+        }
+    }
+
+    /// 1 for a perfect estimate, down to 0 the further off it was.
+    pub fn accuracy(&self) -> f64 {
+        let largest = self.estimate.max(self.actual);
+        match largest {
+            0 => 1.0,
+            _ => 1.0 - self.error().unsigned_abs() as f64 / f64::from(largest),
+        }
+    }
+}
+
+/// Estimates of the done tasks, in the order they were done.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EstimateReport {
+    pub estimates: Vec<Estimate>,
+}
+
+impl EstimateReport {
+    /// Only done tasks with an estimate are compared, the others may still change.
+    pub fn new(tasks: &[Task]) -> Self {
+        let mut done: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| task.is_done() && task.estimate.is_some())
+            .collect();
+        done.sort_by_key(|task| task.done);
+        EstimateReport {
+            estimates: done
+                .into_iter()
+                .map(|task| Estimate {
+                    name: task.name.clone(),
+                    estimate: task.estimate.unwrap_or_default(),
+                    actual: task.actual,
+                })
+                .collect(),
+        }
+    }
+
+    /// Mean accuracy of the last `ROLLING_TASKS` tasks, `None` without tasks.
+    pub fn accuracy(&self) -> Option<f64> {
+        self.rolling().last().copied()
+    }
+
+    /// Rolling accuracy after each task, to see if the estimates get better.
+    pub fn rolling(&self) -> Vec<f64> {
+        (1..=self.estimates.len())
+            .map(|end| {
+                let window = &self.estimates[end.saturating_sub(ROLLING_TASKS)..end];
+                window.iter().map(Estimate::accuracy).sum::<f64>() / window.len() as f64
+            })
+            .collect()
+    }
+
+    /// Tasks in each of the `BUCKETS`, the first and last include the errors
+    /// further off.
+    pub fn histogram(&self) -> [u64; 7] {
+        let mut buckets = [0; 7];
+        for estimate in &self.estimates {
+            buckets[(estimate.error().clamp(-3, 3) + 3) as usize] += 1;
+        }
+        buckets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Local};
+
+    fn task(id: u32, estimate: Option<u32>, actual: u32, done: bool) -> Task {
+        Task {
+            id,
+            name: format!("task {}", id),
+            estimate,
+            actual,
+            created: Local::now(),
+            done: done.then(|| Local::now() + Duration::minutes(id.into())),
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let tasks = vec![
+            task(1, Some(2), 4, true),
+            task(2, Some(3), 3, true),
+            task(3, None, 3, true),
+            task(4, Some(2), 1, false),
+            task(5, Some(5), 1, true),
+        ];
+        let report = EstimateReport::new(&tasks);
+        let errors: Vec<i64> = report.estimates.iter().map(Estimate::error).collect();
+        assert_eq!(errors, vec![2, 0, -4]);
+        assert_eq!(report.histogram(), [1, 0, 0, 1, 0, 1, 0]);
+
+        let rolling = report.rolling();
+        assert_eq!(rolling[0], 0.5);
+        assert_eq!(rolling[1], 0.75);
+        assert!((report.accuracy().unwrap() - (0.5 + 1.0 + 0.2) / 3.0).abs() < 1e-9);
+        assert_eq!(EstimateReport::default().accuracy(), None);
+    }
+}
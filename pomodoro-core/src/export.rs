@@ -0,0 +1,221 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::history::{Outcome, Phase, Record};
+
+// longest line of an iCalendar file, in bytes without the line break
+const ICS_LINE: usize = 75;
+
+/// Output formats of [`write`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One row per record with a header, tags separated by `;`.
+    Csv,
+    /// Array of the records as they are stored in the history.
+    Json,
+    /// iCalendar with one event per pomodoro, breaks are left out.
+    Ics,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 3] = ["csv", "json", "ics"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "ics" => Ok(Format::Ics),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Ics => "ics",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Writes `records` to `out` in `format`.
+pub fn write(records: &[Record], format: Format, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Csv => write_csv(records, out),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+            writeln!(out)
+        }
+        Format::Ics => write_ics(records, out),
+    }
+}
+
+fn write_csv(records: &[Record], out: &mut impl Write) -> io::Result<()> {
+    // the same quoting rules as the csv import
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(["start", "end", "kind", "outcome", "task", "tags"])?;
+    for record in records {
+        writer.write_record([
+            record.start.to_rfc3339().as_str(),
+            record.end.to_rfc3339().as_str(),
+            record.kind.as_str(),
+            record.outcome.as_str(),
+            record.task.as_deref().unwrap_or_default(),
+            record.tags.join(";").as_str(),
+        ])?;
+    }
+    writer.flush()
+}
+
+fn write_ics(records: &[Record], out: &mut impl Write) -> io::Result<()> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//pomodoro-rust//pomodoro//EN".to_string(),
+    ];
+    for record in records.iter().filter(|r| r.kind == Phase::Pomodoro) {
+        let mut summary = record
+            .task
+            .clone()
+            .unwrap_or_else(|| "Pomodoro".to_string());
+        if record.outcome != Outcome::Completed {
+            summary.push_str(&format!(" ({})", record.outcome.as_str()));
+        }
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-pomodoro@pomodoro-rust",
+            record.start.timestamp()
+        ));
+        // the record is never modified after the end of the pomodoro
+        lines.push(format!("DTSTAMP:{}", ics_time(&record.end)));
+        lines.push(format!("DTSTART:{}", ics_time(&record.start)));
+        lines.push(format!("DTEND:{}", ics_time(&record.end)));
+        lines.push(format!("SUMMARY:{}", ics_text(&summary)));
+        if !record.tags.is_empty() {
+            let tags: Vec<String> = record.tags.iter().map(|tag| ics_text(tag)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(out, "{}\r\n", fold(&line))?;
+    }
+    Ok(())
+}
+
+fn ics_time<T: TimeZone>(time: &DateTime<T>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// splits lines longer than `ICS_LINE` bytes, the next lines start with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > ICS_LINE {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap().into();
+        let mut pomodoro = Record::new(
+            Phase::Pomodoro,
+            start,
+            start + chrono::Duration::minutes(25),
+            Outcome::Completed,
+        );
+        pomodoro.task = Some("review, then merge".to_string());
+        pomodoro.tags = vec!["work".to_string(), "review".to_string()];
+        let pause = Record::new(
+            Phase::Break,
+            pomodoro.end,
+            pomodoro.end + chrono::Duration::minutes(5),
+            Outcome::Completed,
+        );
+        vec![pomodoro, pause]
+    }
+
+    fn export(format: Format) -> String {
+        let mut out = Vec::new();
+        write(&records(), format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = export(Format::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "start,end,kind,outcome,task,tags");
+        assert!(lines[1].ends_with(",pomodoro,completed,\"review, then merge\",work;review"));
+        assert!(lines[2].ends_with(",break,completed,,"));
+    }
+
+    #[test]
+    fn test_csv_import_round_trip() {
+        use crate::import::{import, CsvColumns, Source};
+        let csv = export(Format::Csv);
+        let imported = import(&Source::Csv(CsvColumns::default()), &csv, &[]).unwrap();
+        let tasks: Vec<_> = imported.records.iter().map(|r| r.task.clone()).collect();
+        let expected: Vec<_> = records().iter().map(|r| r.task.clone()).collect();
+        assert_eq!(tasks, expected);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let json = export(Format::Json);
+        let records: Vec<Record> = serde_json::from_str(&json).unwrap();
+        assert_eq!(records, self::records());
+    }
+
+    #[test]
+    fn test_ics() {
+        let ics = export(Format::Ics);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("DTSTART:20240301T090000Z\r\n"));
+        assert!(ics.contains("DTEND:20240301T092500Z\r\n"));
+        assert!(ics.contains("SUMMARY:review\\, then merge\r\n"));
+        assert!(ics.contains("CATEGORIES:work,review\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = "x".repeat(80);
+        assert_eq!(
+            fold(&line),
+            format!("{}\r\n {}", "x".repeat(75), "x".repeat(5))
+        );
+    }
+}
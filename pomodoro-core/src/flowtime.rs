@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Break rules of the flowtime mode: the focus lasts until the user stops and
+/// the break is a part of it, like 1/5 capped at 15 minutes.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowtimeConfig {
+    /// Part of the focus time given to the break, 0.2 if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_ratio: Option<f64>,
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_break: Option<Duration>,
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_break: Option<Duration>,
+}
+
+const DEFAULT_BREAK_RATIO: f64 = 0.2;
+
+impl FlowtimeConfig {
+    /// Values of `self`, the unset ones taken from `base`.
+    pub fn or(&self, base: &FlowtimeConfig) -> FlowtimeConfig {
+        FlowtimeConfig {
+            break_ratio: self.break_ratio.or(base.break_ratio),
+            min_break: self.min_break.or(base.min_break),
+            max_break: self.max_break.or(base.max_break),
+        }
+    }
+
+    pub fn is_unset(&self) -> bool {
+        *self == FlowtimeConfig::default()
+    }
+
+    /// Length of the break after `focus`, in whole seconds.
+    pub fn break_after(&self, focus: Duration) -> Duration {
+        let ratio = self.break_ratio.unwrap_or(DEFAULT_BREAK_RATIO).max(0.0);
+        let mut length = Duration::from_secs((focus.as_secs_f64() * ratio).round() as u64);
+        if let Some(max) = self.max_break {
+            length = length.min(max);
+        }
+        if let Some(min) = self.min_break {
+            length = length.max(min);
+        }
+        length
+    }
+}
+
+/// Count-up stopwatch of a flowtime focus, it can be paused.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stopwatch {
+    // time counted before the last start
+    counted: Duration,
+    running_since: Option<Instant>,
+}
+
+impl Stopwatch {
+    pub fn start(&mut self) {
+        self.running_since.get_or_insert_with(Instant::now);
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.counted += since.elapsed();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.counted
+            + self
+                .running_since
+                .map_or(Duration::ZERO, |since| since.elapsed())
+    }
+
+    /// Stops and returns the time counted.
+    pub fn take(&mut self) -> Duration {
+        let elapsed = self.elapsed();
+        *self = Stopwatch::default();
+        elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_break_after() {
+        let minutes = |n: u64| Duration::from_secs(n * 60);
+        let rules = FlowtimeConfig {
+            max_break: Some(minutes(15)),
+            ..Default::default()
+        };
+        assert_eq!(rules.break_after(minutes(40)), minutes(8));
+        assert_eq!(rules.break_after(minutes(120)), minutes(15));
+
+        let rules = FlowtimeConfig {
+            break_ratio: Some(0.25),
+            min_break: Some(minutes(5)),
+            ..Default::default()
+        };
+        assert_eq!(rules.break_after(minutes(10)), minutes(5));
+        assert_eq!(rules.break_after(minutes(60)), minutes(15));
+    }
+
+    #[test]
+    fn test_stopwatch() {
+        let mut stopwatch = Stopwatch::default();
+        stopwatch.start();
+        stopwatch.pause();
+        let counted = stopwatch.elapsed();
+        assert!(!stopwatch.is_running());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(stopwatch.elapsed(), counted);
+        stopwatch.start();
+        assert!(stopwatch.take() >= counted);
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::calendar::Calendar;
+use crate::history::Record;
+use crate::stats;
+
+/// Characters of the five shades, from no pomodoros to the busiest days.
+pub const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+pub const ASCII_SHADES: [char; 5] = ['.', '-', '+', '*', '#'];
+
+// a year of weeks, the same as the github contributions graph
+pub const YEAR_WEEKS: u64 = 53;
+
+// width of the week day labels
+const LABEL_WIDTH: usize = 4;
+
+/// Completed pomodoros per day, one column per week and one row per week day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap {
+    /// Monday of the first column.
+    first: NaiveDate,
+    last: NaiveDate,
+    counts: BTreeMap<NaiveDate, u64>,
+    max: u64,
+}
+
+impl Heatmap {
+    /// Heatmap of the `weeks` weeks ending with the week of `last`.
+    pub fn new(records: &[Record], calendar: &Calendar, last: NaiveDate, weeks: u64) -> Self {
+        let first = calendar.week_of(last) - Days::new(weeks.saturating_sub(1) * 7);
+        let counts: BTreeMap<NaiveDate, u64> = stats::completed_by_day(records, calendar)
+            .into_iter()
+            .filter(|(day, _)| (first..=last).contains(day))
+            .collect();
+        let max = counts.values().copied().max().unwrap_or(0);
+        Heatmap {
+            first,
+            last,
+            counts,
+            max,
+        }
+    }
+
+    /// Weeks that fit in `width` columns, labels included.
+    pub fn weeks_for_width(width: u16) -> u64 {
+        (usize::from(width).saturating_sub(LABEL_WIDTH) as u64).clamp(1, YEAR_WEEKS)
+    }
+
+    pub fn count(&self, day: NaiveDate) -> u64 {
+        self.counts.get(&day).copied().unwrap_or(0)
+    }
+
+    /// Shade of `day` between 0 (nothing) and 4 (the busiest day).
+    pub fn level(&self, day: NaiveDate) -> usize {
+        match self.count(day) {
+            0 => 0,
+            count => (count * 4).div_ceil(self.max) as usize,
+        }
+    }
+
+    /// Month names, seven week days and the legend.
+    pub fn lines(&self, shades: &[char; 5]) -> Vec<String> {
+        let weeks: Vec<NaiveDate> = self
+            .first
+            .iter_weeks()
+            .take_while(|w| *w <= self.last)
+            .collect();
+
+        // month name above the first week of each month
+        let mut months = " ".repeat(LABEL_WIDTH);
+        let mut skip = 0;
+        for (i, week) in weeks.iter().enumerate() {
+            if skip > 0 {
+                skip -= 1;
+            } else if i == 0 || week.month() != weeks[i - 1].month() {
+                let name = week.format("%b").to_string();
+                skip = name.len() - 1;
+                months.push_str(&name);
+            } else {
+                months.push(' ');
+            }
+        }
+        let mut lines = vec![months.trim_end().to_string()];
+
+        for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+            let mut line = format!("{:<width$}", label, width = LABEL_WIDTH);
+            for week in &weeks {
+                let day = *week + Days::new(weekday as u64);
+                if day > self.last {
+                    break;
+                }
+                line.push(shades[self.level(day)]);
+            }
+            lines.push(line);
+        }
+
+        let legend: String = shades.iter().flat_map(|shade| [*shade, ' ']).collect();
+        lines.push(format!("{}Less {}More", " ".repeat(LABEL_WIDTH), legend));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{Outcome, Phase};
+    use chrono::{Local, TimeZone};
+
+    fn pomodoros(day: u32, count: usize) -> Vec<Record> {
+        let start = Local.with_ymd_and_hms(2024, 3, day, 9, 0, 0).unwrap();
+        let record = Record::new(
+            Phase::Pomodoro,
+            start,
+            start + chrono::Duration::minutes(25),
+            Outcome::Completed,
+        );
+        vec![record; count]
+    }
+
+    #[test]
+    fn test_levels() {
+        // 2024-03-06 is a wednesday
+        let last = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let mut records = pomodoros(4, 8);
+        records.extend(pomodoros(5, 1));
+        records.extend(pomodoros(6, 4));
+        let heatmap = Heatmap::new(&records, &Calendar::default(), last, 2);
+        let march = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        assert_eq!(heatmap.first, NaiveDate::from_ymd_opt(2024, 2, 26).unwrap());
+        assert_eq!(heatmap.level(march(3)), 0);
+        assert_eq!(heatmap.level(march(4)), 4);
+        assert_eq!(heatmap.level(march(5)), 1);
+        assert_eq!(heatmap.level(last), 2);
+
+        let lines = heatmap.lines(&ASCII_SHADES);
+        // "Mar" doesn't fit after "Feb"
+        assert_eq!(lines[0], "    Feb");
+        assert_eq!(lines[1], "Mon .#");
+        assert_eq!(lines[3], "Wed .+");
+        // days after `last` are not drawn
+        assert_eq!(lines[4], "    .");
+    }
+}
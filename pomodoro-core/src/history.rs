@@ -0,0 +1,516 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const HISTORY_FILE: &str = "history.jsonl";
+// next to the history file
+const RUNNING_FILE: &str = "running.json";
+const MARKS_FILE: &str = "marks.jsonl";
+
+/// Kind of a recorded phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Pomodoro,
+    Break,
+}
+
+/// How a recorded phase ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The timer reached zero.
+    Completed,
+    /// The program was closed before the end of the phase.
+    Interrupted,
+    /// Abandoned on purpose, or paused too long: it doesn't count.
+    Voided,
+    /// Ended early to move on to the next phase.
+    Skipped,
+    /// Started over: the time spent is kept but doesn't count.
+    Reset,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 2] = [Phase::Pomodoro, Phase::Break];
+
+    /// Name in the history, the exports and the frontends.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Pomodoro => "pomodoro",
+            Phase::Break => "break",
+        }
+    }
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 5] = [
+        Outcome::Completed,
+        Outcome::Interrupted,
+        Outcome::Voided,
+        Outcome::Skipped,
+        Outcome::Reset,
+    ];
+
+    /// Name in the history, the exports and the frontends.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Completed => "completed",
+            Outcome::Interrupted => "interrupted",
+            Outcome::Voided => "voided",
+            Outcome::Skipped => "skipped",
+            Outcome::Reset => "reset",
+        }
+    }
+}
+
+/// Who broke the focus, marked `'` and `-` on paper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionKind {
+    /// The urge to do something else.
+    Internal,
+    /// Somebody or something else.
+    External,
+}
+
+impl InterruptionKind {
+    pub fn symbol(self) -> char {
+        match self {
+            InterruptionKind::Internal => '\'',
+            InterruptionKind::External => '-',
+        }
+    }
+}
+
+impl FromStr for InterruptionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "internal" | "'" => Ok(InterruptionKind::Internal),
+            "external" | "-" => Ok(InterruptionKind::External),
+            _ => Err(format!("unknown interruption '{}'", s)),
+        }
+    }
+}
+
+/// An interruption during a pomodoro.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    pub at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Interruption {
+    pub fn now(kind: InterruptionKind, note: Option<String>) -> Self {
+        Interruption {
+            kind,
+            at: Local::now(),
+            note,
+        }
+    }
+}
+
+/// Reads a focus rating, `None` for an empty text.
+pub fn parse_focus_rating(text: &str) -> Result<Option<u8>, String> {
+    match text.trim() {
+        "" => Ok(None),
+        text => match text.parse() {
+            Ok(rating @ 1..=5) => Ok(Some(rating)),
+            _ => Err(format!("'{}' is not a rating from 1 to 5", text)),
+        },
+    }
+}
+
+/// Phase in progress in one of the frontends, seen by the other ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Running {
+    pub kind: Phase,
+    pub start: DateTime<Local>,
+}
+
+/// One pomodoro or break.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub kind: Phase,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub outcome: Outcome,
+    /// What the pomodoro was about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// What the pomodoro was going to be used for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intention: Option<String>,
+    /// What got done, written at the end.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<String>,
+    /// How focused the pomodoro was, from 1 to 5.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_rating: Option<u8>,
+    /// Time added to the planned length while the phase was running.
+    #[serde(
+        default,
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub extended: Option<Duration>,
+    /// Time past the planned end, counted until the user moved on.
+    #[serde(
+        default,
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub overtime: Option<Duration>,
+}
+
+impl Record {
+    /// Record with just the times and the outcome.
+    pub fn new(
+        kind: Phase,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        outcome: Outcome,
+    ) -> Self {
+        Record {
+            kind,
+            start,
+            end,
+            outcome,
+            task: None,
+            tags: Vec::new(),
+            interruptions: Vec::new(),
+            intention: None,
+            reflection: None,
+            focus_rating: None,
+            extended: None,
+            overtime: None,
+        }
+    }
+
+    pub fn interruptions_of(&self, kind: InterruptionKind) -> usize {
+        self.interruptions
+            .iter()
+            .filter(|interruption| interruption.kind == kind)
+            .count()
+    }
+
+    /// Focus rating, intention and reflection in one line, `None` without any.
+    pub fn notes(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(rating) = self.focus_rating {
+            parts.push(format!("[{}/5]", rating));
+        }
+        match (&self.intention, &self.reflection) {
+            (Some(intention), Some(reflection)) => {
+                parts.push(format!("{} -> {}", intention, reflection))
+            }
+            (Some(text), None) | (None, Some(text)) => parts.push(text.clone()),
+            (None, None) => {}
+        }
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// Wall clock time between start and end.
+    pub fn duration(&self) -> Duration {
+        (self.end - self.start).to_std().unwrap_or_default()
+    }
+}
+
+/// Errors returned while reading or writing the history.
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(PathBuf, io::Error),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    /// There is no data directory on this system.
+    NoPath,
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Io(path, e) => write!(f, "cannot access {}: {}", path.display(), e),
+            HistoryError::Parse { path, line, source } => {
+                write!(f, "invalid record {}:{}: {}", path.display(), line, source)
+            }
+            HistoryError::NoPath => write!(f, "no data directory, set {}", crate::DATA_DIR_ENV),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// The recorded phases, one json object per line.
+///
+/// The phase in progress and its interruptions are kept in two files next to
+/// the history, so they can be marked from another shell.
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        History { path: path.into() }
+    }
+
+    /// History file in the data directory.
+    pub fn open() -> Result<Self, HistoryError> {
+        let dir = crate::data_dir().ok_or(HistoryError::NoPath)?;
+        Ok(History::new(dir.join(HISTORY_FILE)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Adds `record` at the end of the file.
+    pub fn append(&self, record: &Record) -> Result<(), HistoryError> {
+        append_line(&self.path, record)
+    }
+
+    /// Adds `records` among the recorded ones, in the order of their start,
+    /// for records of the past like the imported ones.
+    pub fn insert(&self, records: &[Record]) -> Result<(), HistoryError> {
+        let mut all = self.load()?;
+        all.extend_from_slice(records);
+        // stable, the records starting together keep their order
+        all.sort_by_key(|record| record.start);
+        create_parent(&self.path)?;
+        write_lines(&self.path, &all)
+    }
+
+    /// Reads every record, oldest first. A missing file is an empty history.
+    pub fn load(&self) -> Result<Vec<Record>, HistoryError> {
+        read_lines(&self.path)
+    }
+
+    /// Saves the phase in progress, `None` once it's over.
+    pub fn set_running(&self, running: Option<&Running>) -> Result<(), HistoryError> {
+        let path = self.sibling(RUNNING_FILE);
+        let io_err = |e| HistoryError::Io(path.clone(), e);
+        match running {
+            Some(running) => {
+                create_parent(&path)?;
+                let content = serde_json::to_string(running).expect("always valid json");
+                fs::write(&path, content).map_err(io_err)
+            }
+            None => match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(io_err(e)),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    /// Phase in progress in any frontend.
+    pub fn running(&self) -> Result<Option<Running>, HistoryError> {
+        // the file has a single line
+        Ok(read_lines(&self.sibling(RUNNING_FILE))?.pop())
+    }
+
+    /// Logs an interruption of the running pomodoro.
+    pub fn mark(&self, interruption: &Interruption) -> Result<(), HistoryError> {
+        append_line(&self.sibling(MARKS_FILE), interruption)
+    }
+
+    /// Interruptions logged since `start`, oldest first.
+    pub fn marks_since(&self, start: DateTime<Local>) -> Result<Vec<Interruption>, HistoryError> {
+        let mut marks: Vec<Interruption> = read_lines(&self.sibling(MARKS_FILE))?;
+        marks.retain(|mark| mark.at >= start);
+        Ok(marks)
+    }
+
+    /// Interruptions logged since `start`, removed from the file. The older
+    /// ones are kept for the frontend whose pomodoro they belong to.
+    pub fn take_marks(&self, start: DateTime<Local>) -> Result<Vec<Interruption>, HistoryError> {
+        let path = self.sibling(MARKS_FILE);
+        let (marks, kept): (Vec<Interruption>, _) = read_lines(&path)?
+            .into_iter()
+            .partition(|mark: &Interruption| mark.at >= start);
+        if !marks.is_empty() {
+            write_lines(&path, &kept)?;
+        }
+        Ok(marks)
+    }
+
+    fn sibling(&self, name: &str) -> PathBuf {
+        self.path.with_file_name(name)
+    }
+}
+
+fn create_parent(path: &Path) -> Result<(), HistoryError> {
+    match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).map_err(|e| HistoryError::Io(path.to_path_buf(), e)),
+        None => Ok(()),
+    }
+}
+
+fn append_line(path: &Path, value: &impl Serialize) -> Result<(), HistoryError> {
+    create_parent(path)?;
+    let mut line = serde_json::to_string(value).expect("always valid json");
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| HistoryError::Io(path.to_path_buf(), e))
+}
+
+// replaces the content of `path` with `values`, one per line, through a
+// temporary file so that a failed write leaves the old content
+fn write_lines(path: &Path, values: &[impl Serialize]) -> Result<(), HistoryError> {
+    let mut content = String::new();
+    for value in values {
+        content.push_str(&serde_json::to_string(value).expect("always valid json"));
+        content.push('\n');
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, content)
+        .and_then(|()| fs::rename(&temp, path))
+        .map_err(|e| HistoryError::Io(path.to_path_buf(), e))
+}
+
+// one json value per line, a missing file has no values
+fn read_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, HistoryError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(HistoryError::Io(path.to_path_buf(), e)),
+    };
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line).map_err(|source| HistoryError::Parse {
+                path: path.to_path_buf(),
+                line: n + 1,
+                source,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone};
+
+    #[test]
+    fn test_append_and_load() {
+        let path = std::env::temp_dir().join("pomodoro-core-history-test.jsonl");
+        let _ = fs::remove_file(&path);
+        let history = History::new(&path);
+        assert!(history.load().unwrap().is_empty());
+
+        let start = Local.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let mut record = Record::new(
+            Phase::Pomodoro,
+            start,
+            start + chrono::Duration::minutes(25),
+            Outcome::Completed,
+        );
+        history.append(&record).unwrap();
+        record.task = Some("write the docs".to_string());
+        record.tags = vec!["docs".to_string()];
+        record.intention = Some("write the docs".to_string());
+        record.focus_rating = parse_focus_rating(" 4 ").unwrap();
+        history.append(&record).unwrap();
+
+        let records = history.load().unwrap();
+        assert_eq!(records[1], record);
+        assert_eq!(records[0].task, None);
+        assert_eq!(records[0].notes(), None);
+        assert_eq!(records[1].notes().as_deref(), Some("[4/5] write the docs"));
+        assert_eq!(records[0].duration(), Duration::from_secs(25 * 60));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_insert_keeps_order() {
+        let path = std::env::temp_dir().join("pomodoro-core-insert-test.jsonl");
+        let _ = fs::remove_file(&path);
+        let history = History::new(&path);
+        let pomodoro = |day| {
+            let start = Local.with_ymd_and_hms(2024, 3, day, 9, 0, 0).unwrap();
+            let end = start + chrono::Duration::minutes(25);
+            Record::new(Phase::Pomodoro, start, end, Outcome::Completed)
+        };
+        history.append(&pomodoro(10)).unwrap();
+        history.append(&pomodoro(12)).unwrap();
+        history.insert(&[pomodoro(11), pomodoro(1)]).unwrap();
+
+        let days: Vec<u32> = history
+            .load()
+            .unwrap()
+            .iter()
+            .map(|record| record.start.day())
+            .collect();
+        assert_eq!(days, vec![1, 10, 11, 12]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_focus_rating() {
+        assert_eq!(parse_focus_rating("5"), Ok(Some(5)));
+        assert_eq!(parse_focus_rating(""), Ok(None));
+        assert!(parse_focus_rating("0").is_err());
+        assert!(parse_focus_rating("great").is_err());
+    }
+
+    #[test]
+    fn test_marks() {
+        let dir = std::env::temp_dir().join("pomodoro-core-marks-test");
+        let _ = fs::remove_dir_all(&dir);
+        let history = History::new(dir.join(HISTORY_FILE));
+        assert_eq!(history.running().unwrap(), None);
+
+        let start = Local::now();
+        let running = Running {
+            kind: Phase::Pomodoro,
+            start,
+        };
+        history.set_running(Some(&running)).unwrap();
+        assert_eq!(history.running().unwrap(), Some(running));
+
+        let mut stale = Interruption::now(InterruptionKind::External, None);
+        stale.at = start - chrono::Duration::hours(1);
+        history.mark(&stale).unwrap();
+        let note = Some("mail".to_string());
+        history
+            .mark(&Interruption::now(InterruptionKind::Internal, note))
+            .unwrap();
+        assert_eq!(history.marks_since(start).unwrap().len(), 1);
+
+        let marks = history.take_marks(start).unwrap();
+        assert_eq!(marks[0].note.as_deref(), Some("mail"));
+        assert!(history.take_marks(start).unwrap().is_empty());
+        // the stale mark is still there for its own pomodoro
+        assert_eq!(history.take_marks(stale.at).unwrap(), vec![stale.clone()]);
+        assert!(history.take_marks(stale.at).unwrap().is_empty());
+        history.set_running(None).unwrap();
+        assert_eq!(history.running().unwrap(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,531 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{
+    DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::duration::parse_duration;
+use crate::plan::Block;
+
+/// Events of an iCalendar file, read leniently: an event that can't be
+/// understood is left out rather than failing the whole calendar.
+///
+/// All-day, free (`TRANSP:TRANSPARENT`) and cancelled events don't make the
+/// day busy and are left out too. Repeated events follow their `RRULE`
+/// (daily, weekly, monthly on the same day or yearly) and `EXDATE`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Events {
+    events: Vec<Event>,
+    // occurrences replaced by an event of their own, by uid
+    moved: HashSet<(String, NaiveDate)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    uid: Option<String>,
+    summary: Option<String>,
+    start: Stamp,
+    length: chrono::Duration,
+    rule: Option<Rule>,
+    // dates of the skipped occurrences, in the zone of the start
+    except: Vec<NaiveDate>,
+}
+
+// a date and time on the wall clock of a zone
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stamp {
+    time: NaiveDateTime,
+    zone: Zone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Tz(Tz),
+    /// Floating time, or a `TZID` chrono-tz doesn't know.
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+    days: Vec<Weekday>,
+}
+
+impl Stamp {
+    fn local(&self) -> Option<DateTime<Local>> {
+        let time = match self.zone {
+            Zone::Utc => Utc.from_utc_datetime(&self.time).with_timezone(&Local),
+            Zone::Tz(tz) => earliest(tz.from_local_datetime(&self.time))?.with_timezone(&Local),
+            Zone::Local => earliest(Local.from_local_datetime(&self.time))?,
+        };
+        Some(time)
+    }
+
+    fn on(&self, date: NaiveDate) -> Stamp {
+        Stamp {
+            time: date.and_time(self.time.time()),
+            ..*self
+        }
+    }
+}
+
+// the time before the change of a DST fold, `None` in a gap
+fn earliest<T: TimeZone>(time: LocalResult<DateTime<T>>) -> Option<DateTime<T>> {
+    match time {
+        LocalResult::Single(time) => Some(time),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest),
+        LocalResult::None => None,
+    }
+}
+
+impl Rule {
+    // whether the rule repeats an event starting on `first` on `date`
+    fn matches(&self, first: NaiveDate, date: NaiveDate) -> bool {
+        let interval = self.interval.max(1) as i64;
+        match self.frequency {
+            Frequency::Daily => {
+                (date - first).num_days() % interval == 0
+                    && (self.days.is_empty() || self.days.contains(&date.weekday()))
+            }
+            Frequency::Weekly => {
+                let weeks = (date.week(Weekday::Mon).first_day()
+                    - first.week(Weekday::Mon).first_day())
+                .num_weeks();
+                let day = match self.days.is_empty() {
+                    true => date.weekday() == first.weekday(),
+                    false => self.days.contains(&date.weekday()),
+                };
+                weeks % interval == 0 && day
+            }
+            Frequency::Monthly => {
+                let months = (date.year() - first.year()) as i64 * 12 + date.month() as i64
+                    - first.month() as i64;
+                months % interval == 0 && date.day() == first.day()
+            }
+            Frequency::Yearly => {
+                (date.year() - first.year()) as i64 % interval == 0
+                    && date.month() == first.month()
+                    && date.day() == first.day()
+            }
+        }
+    }
+
+    fn occurs(&self, first: NaiveDate, date: NaiveDate) -> bool {
+        if date < first || self.until.is_some_and(|until| date > until) {
+            return false;
+        }
+        match self.count {
+            // the occurrences before this one count too
+            Some(count) => {
+                let done = first
+                    .iter_days()
+                    .take_while(|day| *day < date)
+                    .filter(|day| self.matches(first, *day))
+                    .take(count as usize)
+                    .count();
+                (done as u32) < count && self.matches(first, date)
+            }
+            None => self.matches(first, date),
+        }
+    }
+}
+
+impl Event {
+    // starts of the occurrences beginning on `date`, in the zone of the event
+    fn starts_on(&self, date: NaiveDate) -> Option<Stamp> {
+        let first = self.start.time.date();
+        let occurs = match &self.rule {
+            Some(rule) => rule.occurs(first, date) && !self.except.contains(&date),
+            None => date == first,
+        };
+        occurs.then(|| self.start.on(date))
+    }
+}
+
+impl Events {
+    /// Reads an `.ics` file, or every `.ics` file of a directory like a vdir.
+    pub fn read(path: &Path) -> io::Result<Events> {
+        if !path.is_dir() {
+            return Ok(Events::parse(&fs::read_to_string(path)?));
+        }
+        let mut text = String::new();
+        let mut files: Vec<_> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        files.sort();
+        for file in files {
+            if file
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+            {
+                text.push_str(&fs::read_to_string(file)?);
+                text.push('\n');
+            }
+        }
+        Ok(Events::parse(&text))
+    }
+
+    pub fn parse(text: &str) -> Events {
+        let mut events = Events::default();
+        let mut lines = unfold(text).into_iter();
+        while let Some(line) = lines.next() {
+            if !line.eq_ignore_ascii_case("BEGIN:VEVENT") {
+                continue;
+            }
+            let properties: Vec<Property> = lines
+                .by_ref()
+                .take_while(|line| !line.eq_ignore_ascii_case("END:VEVENT"))
+                .filter_map(|line| Property::parse(&line))
+                .collect();
+            let uid = find(&properties, "UID").map(|p| p.value.clone());
+            if let (Some(uid), Some(moved)) = (&uid, find(&properties, "RECURRENCE-ID")) {
+                if let Some(stamp) = moved.stamp() {
+                    events.moved.insert((uid.clone(), stamp.time.date()));
+                }
+            }
+            if let Some(event) = read_event(uid, &properties) {
+                events.events.push(event);
+            }
+        }
+        events
+    }
+
+    /// The events of `date` as busy blocks, cut at midnight.
+    pub fn busy(&self, date: NaiveDate) -> Vec<Block> {
+        let Some(day_start) = earliest(Local.from_local_datetime(&date.and_time(NaiveTime::MIN)))
+        else {
+            return Vec::new();
+        };
+        let day_end = day_start + chrono::Duration::days(1);
+        let mut blocks = Vec::new();
+        for event in &self.events {
+            // an event of the day before may end after midnight
+            for day in [date.pred_opt(), Some(date), date.succ_opt()]
+                .into_iter()
+                .flatten()
+            {
+                let Some(start) = event.starts_on(day) else {
+                    continue;
+                };
+                let moved = event.rule.is_some()
+                    && event
+                        .uid
+                        .as_ref()
+                        .is_some_and(|uid| self.moved.contains(&(uid.clone(), start.time.date())));
+                let Some(start) = start.local().filter(|_| !moved) else {
+                    continue;
+                };
+                let end = (start + event.length).min(day_end);
+                let start = start.max(day_start);
+                if start >= end {
+                    continue;
+                }
+                blocks.push(Block {
+                    start: start.time(),
+                    // the end of the day is the last minute of it
+                    end: match end == day_end {
+                        true => NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN),
+                        false => end.time(),
+                    },
+                    name: event.summary.clone(),
+                });
+            }
+        }
+        blocks.sort_by_key(|block| block.start);
+        blocks
+    }
+}
+
+// joins the lines folded by a leading space or tab
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+    lines
+}
+
+// `NAME;PARAM=value:value`
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Property> {
+        let (head, value) = line.split_once(':')?;
+        let mut parts = head.split(';');
+        let name = parts.next()?.to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.to_ascii_uppercase(),
+                    value.trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+        Some(Property {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // a date-time, `None` for a date alone
+    fn stamp(&self) -> Option<Stamp> {
+        stamp(self.value.split(',').next()?, self.param("TZID"))
+    }
+
+    // the dates of a list of date-times or dates
+    fn dates(&self) -> Vec<NaiveDate> {
+        self.value
+            .split(',')
+            .filter_map(|value| match stamp(value, self.param("TZID")) {
+                Some(stamp) => Some(stamp.time.date()),
+                None => NaiveDate::parse_from_str(value, "%Y%m%d").ok(),
+            })
+            .collect()
+    }
+}
+
+fn stamp(value: &str, tzid: Option<&str>) -> Option<Stamp> {
+    let (value, utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = match (utc, tzid.and_then(|tzid| tzid.parse::<Tz>().ok())) {
+        (true, _) => Zone::Utc,
+        (false, Some(tz)) => Zone::Tz(tz),
+        (false, None) => Zone::Local,
+    };
+    Some(Stamp { time, zone })
+}
+
+fn find<'a>(properties: &'a [Property], name: &str) -> Option<&'a Property> {
+    properties.iter().find(|property| property.name == name)
+}
+
+fn read_event(uid: Option<String>, properties: &[Property]) -> Option<Event> {
+    let value = |name| find(properties, name).map(|p| p.value.to_ascii_uppercase());
+    if value("TRANSP").as_deref() == Some("TRANSPARENT")
+        || value("STATUS").as_deref() == Some("CANCELLED")
+    {
+        return None;
+    }
+    // all-day events have a date alone
+    let start = find(properties, "DTSTART")?.stamp()?;
+    let length = match (find(properties, "DTEND"), find(properties, "DURATION")) {
+        (Some(end), _) => end.stamp()?.local()? - start.local()?,
+        (None, Some(duration)) => read_duration(&duration.value)?,
+        (None, None) => return None,
+    };
+    if length <= chrono::Duration::zero() {
+        return None;
+    }
+    let rule = match find(properties, "RRULE") {
+        Some(rule) => Some(read_rule(&rule.value)?),
+        None => None,
+    };
+    Some(Event {
+        uid,
+        summary: find(properties, "SUMMARY")
+            .map(|p| p.value.replace("\\,", ",").replace("\\;", ";"))
+            .filter(|summary| !summary.is_empty()),
+        start,
+        length,
+        rule,
+        except: properties
+            .iter()
+            .filter(|p| p.name == "EXDATE")
+            .flat_map(Property::dates)
+            .collect(),
+    })
+}
+
+// `PT1H30M` or `P1D`, the weeks and days as 24 hours
+fn read_duration(text: &str) -> Option<chrono::Duration> {
+    let text = text.strip_prefix('P')?;
+    let (days, time) = text.split_once('T').unwrap_or((text, ""));
+    let days = match days {
+        "" => 0,
+        _ => match days.strip_suffix('W') {
+            Some(weeks) => weeks.parse::<i64>().ok()? * 7,
+            None => days.strip_suffix('D')?.parse().ok()?,
+        },
+    };
+    let time = match time {
+        "" => std::time::Duration::ZERO,
+        _ => parse_duration(&time.to_lowercase()).ok()?,
+    };
+    Some(chrono::Duration::days(days) + chrono::Duration::from_std(time).ok()?)
+}
+
+// the rules the planner understands, `None` for the others
+fn read_rule(text: &str) -> Option<Rule> {
+    let mut frequency = None;
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        days: Vec::new(),
+    };
+    for part in text.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok()?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => {
+                rule.until = Some(NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?)
+            }
+            "BYDAY" => rule.days = value.split(',').map(read_weekday).collect::<Option<_>>()?,
+            "WKST" => {}
+            // BYMONTHDAY, BYSETPOS... would need more than the planner needs
+            _ => return None,
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+// `MO`, `TU`... a day with a number like `1MO` needs more than the planner needs
+fn read_weekday(day: &str) -> Option<Weekday> {
+    Some(match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+UID:standup\r
+SUMMARY:Standup\r
+DTSTART:20261019T100000\r
+DTEND:20261019T101500\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r
+EXDATE:20261023T100000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:review\r
+SUMMARY:Design review\\, with\r
+  the team\r
+DTSTART:20261021T150000\r
+DURATION:PT1H30M\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup\r
+RECURRENCE-ID:20261021T100000\r
+SUMMARY:Standup\r
+DTSTART:20261021T110000\r
+DTEND:20261021T111500\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday\r
+DTSTART;VALUE=DATE:20261021\r
+DTEND;VALUE=DATE:20261022\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn day(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn blocks(events: &Events, date: &str) -> Vec<String> {
+        let blocks = events.busy(day(date));
+        blocks.iter().map(|block| block.to_string()).collect()
+    }
+
+    #[test]
+    fn test_busy() {
+        let events = Events::parse(CALENDAR);
+        assert_eq!(blocks(&events, "2026-10-19"), vec!["10:00-10:15 Standup"]);
+        // the standup moved, the all-day event doesn't count
+        assert_eq!(
+            blocks(&events, "2026-10-21"),
+            vec![
+                "11:00-11:15 Standup",
+                "15:00-16:30 Design review, with the team"
+            ]
+        );
+        assert!(blocks(&events, "2026-10-20").is_empty());
+        assert!(blocks(&events, "2026-10-23").is_empty());
+        assert_eq!(blocks(&events, "2026-10-26"), vec!["10:00-10:15 Standup"]);
+    }
+
+    #[test]
+    fn test_rules() {
+        let rule = read_rule("FREQ=DAILY;INTERVAL=2;COUNT=3").unwrap();
+        let first = day("2026-10-01");
+        let days: Vec<u32> = first
+            .iter_days()
+            .take(10)
+            .filter(|date| rule.occurs(first, *date))
+            .map(|date| date.day())
+            .collect();
+        assert_eq!(days, vec![1, 3, 5]);
+
+        let rule = read_rule("FREQ=MONTHLY;UNTIL=20261231T000000Z").unwrap();
+        assert!(rule.occurs(first, day("2026-12-01")));
+        assert!(!rule.occurs(first, day("2027-01-01")));
+        assert!(read_rule("FREQ=MONTHLY;BYSETPOS=-1").is_none());
+    }
+
+    #[test]
+    fn test_overnight() {
+        let events = Events::parse(
+            "BEGIN:VEVENT\nDTSTART:20261019T230000\nDURATION:PT2H\nSUMMARY:Release\nEND:VEVENT\n",
+        );
+        assert_eq!(blocks(&events, "2026-10-19"), vec!["23:00-23:59 Release"]);
+        assert_eq!(blocks(&events, "2026-10-20"), vec!["00:00-01:00 Release"]);
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::duration::parse_duration;
+use crate::history::{Outcome, Phase, Record};
+
+// keywords dropped from the start of org headings
+const ORG_KEYWORDS: [&str; 2] = ["TODO", "DONE"];
+// error of a record ending before it starts
+const NOT_AFTER_START: &str = "the end isn't after the start";
+
+/// Where the records come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Csv(CsvColumns),
+    /// `timew export` output.
+    Timewarrior,
+    /// `CLOCK:` lines of an org-mode file, the task is the heading above.
+    Org,
+}
+
+/// Names of the csv columns of each field. Only start and one of end and
+/// duration are required, the defaults match the csv export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvColumns {
+    pub start: String,
+    pub end: String,
+    pub duration: String,
+    pub kind: String,
+    pub outcome: String,
+    pub task: String,
+    pub tags: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        CsvColumns {
+            start: "start".to_string(),
+            end: "end".to_string(),
+            duration: "duration".to_string(),
+            kind: "kind".to_string(),
+            outcome: "outcome".to_string(),
+            task: "task".to_string(),
+            tags: "tags".to_string(),
+        }
+    }
+}
+
+impl CsvColumns {
+    /// Reads `field` from `column`, for instance `task` from `Description`.
+    pub fn set(&mut self, field: &str, column: &str) -> Result<(), ImportError> {
+        let slot = match field {
+            "start" => &mut self.start,
+            "end" => &mut self.end,
+            "duration" => &mut self.duration,
+            "kind" => &mut self.kind,
+            "outcome" => &mut self.outcome,
+            "task" => &mut self.task,
+            "tags" => &mut self.tags,
+            _ => return Err(ImportError::UnknownField(field.to_string())),
+        };
+        *slot = column.to_string();
+        Ok(())
+    }
+}
+
+/// Errors returned while reading records from another tool.
+#[derive(Debug)]
+pub enum ImportError {
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    /// A field of [`CsvColumns::set`] that doesn't exist.
+    UnknownField(String),
+    /// A required csv column isn't in the header.
+    MissingColumn(String),
+    Invalid {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Csv(e) => write!(f, "invalid csv: {}", e),
+            ImportError::Json(e) => write!(f, "invalid json: {}", e),
+            ImportError::UnknownField(field) => write!(f, "unknown field '{}'", field),
+            ImportError::MissingColumn(column) => write!(f, "missing column '{}'", column),
+            ImportError::Invalid { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Records read from another tool.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Import {
+    /// Records that aren't in the history yet, oldest first.
+    pub records: Vec<Record>,
+    /// Records already in the history or twice in the input.
+    pub duplicates: usize,
+    /// Intervals without an end, still being tracked.
+    pub running: usize,
+}
+
+/// Reads the records of `text`, leaving out the ones already in `existing`.
+///
+/// Two records are the same when they have the same kind, start and end.
+pub fn import(source: &Source, text: &str, existing: &[Record]) -> Result<Import, ImportError> {
+    let (mut records, running) = match source {
+        Source::Csv(columns) => (read_csv(columns, text)?, 0),
+        Source::Timewarrior => read_timewarrior(text)?,
+        Source::Org => read_org(text)?,
+    };
+    records.sort_by_key(|record| record.start);
+
+    let mut seen: HashSet<_> = existing.iter().map(key).collect();
+    let total = records.len();
+    records.retain(|record| seen.insert(key(record)));
+    Ok(Import {
+        duplicates: total - records.len(),
+        records,
+        running,
+    })
+}
+
+fn key(record: &Record) -> (Phase, i64, i64) {
+    (
+        record.kind,
+        record.start.timestamp(),
+        record.end.timestamp(),
+    )
+}
+
+fn read_csv(columns: &CsvColumns, text: &str) -> Result<Vec<Record>, ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let header = reader.headers().map_err(ImportError::Csv)?.clone();
+    let index = |name: &str| header.iter().position(|column| column == name);
+    let required =
+        |name: &str| index(name).ok_or_else(|| ImportError::MissingColumn(name.to_string()));
+
+    let start = required(&columns.start)?;
+    let end = match (index(&columns.end), index(&columns.duration)) {
+        (Some(end), _) => Ok(end),
+        (None, Some(duration)) => Err(duration),
+        (None, None) => return Err(ImportError::MissingColumn(columns.end.clone())),
+    };
+    let kind = index(&columns.kind);
+    let outcome = index(&columns.outcome);
+    let task = index(&columns.task);
+    let tags = index(&columns.tags);
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(ImportError::Csv)?;
+        // line in the file, the header is line 1
+        let line = row.position().map_or(0, |p| p.line() as usize);
+        let invalid = |message: String| ImportError::Invalid { line, message };
+        let field = |i: Option<usize>| i.and_then(|i| row.get(i)).filter(|f| !f.is_empty());
+
+        let start = field(Some(start)).unwrap_or_default();
+        let start =
+            parse_time(start).ok_or_else(|| invalid(format!("'{}' is not a time", start)))?;
+        let end = match end {
+            Ok(end) => {
+                let end = field(Some(end)).unwrap_or_default();
+                parse_time(end).ok_or_else(|| invalid(format!("'{}' is not a time", end)))?
+            }
+            Err(duration) => {
+                let duration = field(Some(duration)).unwrap_or_default();
+                let duration = parse_duration(duration)
+                    .map_err(|e| invalid(format!("'{}' is not a duration ({})", duration, e)))?;
+                start + chrono::Duration::from_std(duration).map_err(|e| invalid(e.to_string()))?
+            }
+        };
+        if end <= start {
+            return Err(invalid(NOT_AFTER_START.to_string()));
+        }
+        let kind = match field(kind).map(str::to_ascii_lowercase) {
+            None => Phase::Pomodoro,
+            Some(name) => match Phase::ALL.into_iter().find(|kind| kind.as_str() == name) {
+                Some(kind) => kind,
+                None => return Err(invalid(format!("unknown kind '{}'", name))),
+            },
+        };
+        let outcome = match field(outcome).map(str::to_ascii_lowercase) {
+            None => Outcome::Completed,
+            Some(name) => match Outcome::ALL.into_iter().find(|o| o.as_str() == name) {
+                Some(outcome) => outcome,
+                None => return Err(invalid(format!("unknown outcome '{}'", name))),
+            },
+        };
+
+        let mut record = Record::new(kind, start, end, outcome);
+        record.task = field(task).map(str::to_string);
+        record.tags = field(tags)
+            .map(|tags| tags.split(';').map(|tag| tag.trim().to_string()).collect())
+            .unwrap_or_default();
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// rfc 3339 or a local time without offset
+fn parse_time(text: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Local));
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+}
+
+#[derive(Deserialize)]
+struct Interval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+fn read_timewarrior(text: &str) -> Result<(Vec<Record>, usize), ImportError> {
+    let intervals: Vec<Interval> = serde_json::from_str(text).map_err(ImportError::Json)?;
+    let mut records = Vec::new();
+    let mut running = 0;
+    for (i, interval) in intervals.into_iter().enumerate() {
+        let Some(end) = interval.end else {
+            running += 1;
+            continue;
+        };
+        let time = |text: &str| {
+            NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%SZ")
+                .map(|time| Utc.from_utc_datetime(&time).with_timezone(&Local))
+                .map_err(|_| ImportError::Invalid {
+                    line: i + 1,
+                    message: format!("'{}' is not a time", text),
+                })
+        };
+        let (start, end) = (time(&interval.start)?, time(&end)?);
+        if end <= start {
+            return Err(ImportError::Invalid {
+                line: i + 1,
+                message: NOT_AFTER_START.to_string(),
+            });
+        }
+        let mut record = Record::new(Phase::Pomodoro, start, end, Outcome::Completed);
+        record.task = interval.annotation;
+        record.tags = interval.tags;
+        records.push(record);
+    }
+    Ok((records, running))
+}
+
+fn read_org(text: &str) -> Result<(Vec<Record>, usize), ImportError> {
+    let mut records = Vec::new();
+    let mut running = 0;
+    let mut heading: Option<(String, Vec<String>)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let invalid = |message: String| ImportError::Invalid {
+            line: i + 1,
+            message,
+        };
+        if line.starts_with('*') && line.trim_start_matches('*').starts_with(' ') {
+            heading = Some(org_heading(line));
+            continue;
+        }
+        let Some(clock) = line.trim_start().strip_prefix("CLOCK:") else {
+            continue;
+        };
+        let Some((start, end)) = clock.trim().split_once("--") else {
+            running += 1;
+            continue;
+        };
+        // the end is followed by "=>  0:25"
+        let end = end.split("=>").next().unwrap_or_default();
+        let time = |text: &str| {
+            org_time(text).ok_or_else(|| invalid(format!("'{}' is not a timestamp", text.trim())))
+        };
+
+        let (start, end) = (time(start)?, time(end)?);
+        if end <= start {
+            return Err(invalid(NOT_AFTER_START.to_string()));
+        }
+        let mut record = Record::new(Phase::Pomodoro, start, end, Outcome::Completed);
+        if let Some((task, tags)) = &heading {
+            record.task = Some(task.clone()).filter(|task| !task.is_empty());
+            record.tags = tags.clone();
+        }
+        records.push(record);
+    }
+    Ok((records, running))
+}
+
+// title and tags of "** TODO [#A] Write the docs   :work:docs:"
+fn org_heading(line: &str) -> (String, Vec<String>) {
+    let mut title = line.trim_start_matches('*').trim();
+    let mut tags = Vec::new();
+    if let Some((rest, last)) = title.rsplit_once(char::is_whitespace) {
+        if last.len() > 1 && last.starts_with(':') && last.ends_with(':') {
+            tags = last
+                .split(':')
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect();
+            title = rest.trim_end();
+        }
+    }
+    for keyword in ORG_KEYWORDS {
+        if let Some(rest) = title.strip_prefix(keyword) {
+            if rest.is_empty() || rest.starts_with(' ') {
+                title = rest.trim_start();
+            }
+        }
+    }
+    if title.starts_with("[#") {
+        if let Some((_, rest)) = title.split_once(']') {
+            title = rest.trim_start();
+        }
+    }
+    (title.to_string(), tags)
+}
+
+// "[2024-03-01 Fri 09:00]", the week day is optional
+fn org_time(text: &str) -> Option<DateTime<Local>> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let mut parts = inner.split_whitespace();
+    let date = NaiveDate::parse_from_str(parts.next()?, "%Y-%m-%d").ok()?;
+    let time = parts.last()?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_csv_mapping() {
+        let text = "Begin,Length,What\n\
+                    2024-03-01 09:00,25m,\"review, then merge\"\n\
+                    2024-03-01 10:00,25,\n";
+        let mut columns = CsvColumns::default();
+        columns.set("start", "Begin").unwrap();
+        columns.set("duration", "Length").unwrap();
+        columns.set("task", "What").unwrap();
+        assert!(columns.set("project", "Project").is_err());
+
+        let import = import(&Source::Csv(columns), text, &[]).unwrap();
+        assert_eq!(import.records.len(), 2);
+        assert_eq!(import.records[0].start, local(1, 9, 0));
+        assert_eq!(import.records[0].end, local(1, 9, 25));
+        assert_eq!(
+            import.records[0].task.as_deref(),
+            Some("review, then merge")
+        );
+        assert_eq!(import.records[1].task, None);
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let missing = import(&Source::Csv(CsvColumns::default()), "begin,end\n", &[]);
+        assert!(matches!(missing, Err(ImportError::MissingColumn(c)) if c == "start"));
+        let text = "start,end\n2024-03-01 09:00,2024-03-01 09:25\nyesterday,today\n";
+        let invalid = import(&Source::Csv(CsvColumns::default()), text, &[]);
+        assert!(matches!(invalid, Err(ImportError::Invalid { line: 3, .. })));
+        let text =
+            "start,end\n2024-03-01 09:00,2024-03-01 09:25\n2024-03-01 10:00,2024-03-01 09:00\n";
+        let backwards = import(&Source::Csv(CsvColumns::default()), text, &[]);
+        assert!(matches!(
+            backwards,
+            Err(ImportError::Invalid { line: 3, .. })
+        ));
+        let org = "* Docs\nCLOCK: [2024-03-01 Fri 09:25]--[2024-03-01 Fri 09:00] =>  -0:25\n";
+        let backwards = import(&Source::Org, org, &[]);
+        assert!(matches!(
+            backwards,
+            Err(ImportError::Invalid { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_timewarrior() {
+        let text = r#"[
+            {"id":2,"start":"20240301T080000Z","end":"20240301T082500Z","tags":["work"],"annotation":"docs"},
+            {"id":1,"start":"20240301T090000Z","tags":["work"]}
+        ]"#;
+        let import = import(&Source::Timewarrior, text, &[]).unwrap();
+        assert_eq!(import.running, 1);
+        let record = &import.records[0];
+        assert_eq!(
+            record.start,
+            Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap()
+        );
+        assert_eq!(record.duration().as_secs(), 25 * 60);
+        assert_eq!(record.task.as_deref(), Some("docs"));
+        assert_eq!(record.tags, vec!["work"]);
+    }
+
+    #[test]
+    fn test_org() {
+        let text = "\
+* TODO [#A] Write the docs   :work:docs:
+  :LOGBOOK:
+  CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 09:25] =>  0:25
+  CLOCK: [2024-03-01 Fri 10:00]
+  :END:
+** Review
+CLOCK: [2024-03-02 Sat 11:00]--[2024-03-02 Sat 11:25] =>  0:25
+";
+        let import = import(&Source::Org, text, &[]).unwrap();
+        assert_eq!(import.running, 1);
+        assert_eq!(import.records.len(), 2);
+        assert_eq!(import.records[0].start, local(1, 9, 0));
+        assert_eq!(import.records[0].task.as_deref(), Some("Write the docs"));
+        assert_eq!(import.records[0].tags, vec!["work", "docs"]);
+        assert_eq!(import.records[1].task.as_deref(), Some("Review"));
+        assert!(import.records[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let text = "start,end\n\
+                    2024-03-01 09:00,2024-03-01 09:25\n\
+                    2024-03-01 09:00,2024-03-01 09:25\n\
+                    2024-03-01 10:00,2024-03-01 10:25\n";
+        let existing = vec![Record::new(
+            Phase::Pomodoro,
+            local(1, 10, 0),
+            local(1, 10, 25),
+            Outcome::Completed,
+        )];
+        let import = import(&Source::Csv(CsvColumns::default()), text, &existing).unwrap();
+        assert_eq!(import.records.len(), 1);
+        assert_eq!(import.duplicates, 2);
+    }
+}
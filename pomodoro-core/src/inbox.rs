@@ -0,0 +1,184 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::tasks::{TaskError, TaskList};
+
+const INBOX_FILE: &str = "inbox.json";
+
+/// Something that popped up during a pomodoro, to look at in the next break.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    pub text: String,
+    pub captured: DateTime<Local>,
+}
+
+/// Errors returned while reading or changing the inbox.
+#[derive(Debug)]
+pub enum InboxError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    /// There is no data directory on this system.
+    NoPath,
+    /// No capture at this index, counting from 0.
+    NotFound(usize),
+    EmptyText,
+    Task(TaskError),
+}
+
+impl fmt::Display for InboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InboxError::Io(path, e) => write!(f, "cannot access {}: {}", path.display(), e),
+            InboxError::Parse(path, e) => write!(f, "invalid inbox {}: {}", path.display(), e),
+            InboxError::NoPath => write!(f, "no data directory, set {}", crate::DATA_DIR_ENV),
+            InboxError::NotFound(index) => write!(f, "no capture {}", index.saturating_add(1)),
+            InboxError::EmptyText => write!(f, "nothing to capture"),
+            InboxError::Task(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InboxError {}
+
+impl From<TaskError> for InboxError {
+    fn from(e: TaskError) -> Self {
+        InboxError::Task(e)
+    }
+}
+
+/// The captures not handled yet, saved as a json array after every change.
+///
+/// Other shells capture in the same file, every change reads it again first.
+#[derive(Debug, Clone)]
+pub struct Inbox {
+    path: PathBuf,
+    captures: Vec<Capture>,
+}
+
+impl Inbox {
+    /// Reads the captures of `path`, a missing file is an empty inbox.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, InboxError> {
+        let mut inbox = Inbox {
+            path: path.into(),
+            captures: Vec::new(),
+        };
+        inbox.reload()?;
+        Ok(inbox)
+    }
+
+    /// Inbox in the data directory.
+    pub fn open() -> Result<Self, InboxError> {
+        let dir = crate::data_dir().ok_or(InboxError::NoPath)?;
+        Inbox::load(dir.join(INBOX_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Captures, oldest first.
+    pub fn captures(&self) -> &[Capture] {
+        &self.captures
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.captures.is_empty()
+    }
+
+    /// Reads the file again, to see the captures of other shells.
+    pub fn reload(&mut self) -> Result<(), InboxError> {
+        self.captures = match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| InboxError::Parse(self.path.clone(), e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(InboxError::Io(self.path.clone(), e)),
+        };
+        Ok(())
+    }
+
+    pub fn capture(&mut self, text: &str) -> Result<(), InboxError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(InboxError::EmptyText);
+        }
+        self.reload()?;
+        self.captures.push(Capture {
+            text: text.to_string(),
+            captured: Local::now(),
+        });
+        self.save()
+    }
+
+    /// Drops the capture at `index`, returning it.
+    pub fn discard(&mut self, index: usize) -> Result<Capture, InboxError> {
+        self.reload()?;
+        if index >= self.captures.len() {
+            return Err(InboxError::NotFound(index));
+        }
+        let capture = self.captures.remove(index);
+        self.save()?;
+        Ok(capture)
+    }
+
+    /// Moves the capture at `index` to `tasks`, returning the id of the new task.
+    pub fn into_task(
+        &mut self,
+        index: usize,
+        tasks: &mut TaskList,
+        estimate: Option<u32>,
+    ) -> Result<u32, InboxError> {
+        self.reload()?;
+        let capture = self
+            .captures
+            .get(index)
+            .ok_or(InboxError::NotFound(index))?;
+        let id = tasks.add(&capture.text, estimate)?.id;
+        self.discard(index)?;
+        Ok(id)
+    }
+
+    fn save(&self) -> Result<(), InboxError> {
+        let io_err = |e| InboxError::Io(self.path.clone(), e);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        let content =
+            serde_json::to_string_pretty(&self.captures).expect("captures are always valid json");
+        fs::write(&self.path, content).map_err(io_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbox() {
+        let dir = std::env::temp_dir().join("pomodoro-core-inbox-test");
+        let _ = fs::remove_dir_all(&dir);
+        let mut inbox = Inbox::load(dir.join(INBOX_FILE)).unwrap();
+        assert!(matches!(inbox.capture(" "), Err(InboxError::EmptyText)));
+        inbox.capture("call the bank").unwrap();
+
+        // captured from another shell
+        let mut other = Inbox::load(inbox.path()).unwrap();
+        other.capture("buy milk").unwrap();
+        other.capture("fix the flaky test").unwrap();
+
+        inbox.discard(1).unwrap();
+        let mut tasks = TaskList::load(dir.join("tasks.json")).unwrap();
+        let id = inbox.into_task(1, &mut tasks, Some(2)).unwrap();
+        assert_eq!(tasks.get(id).unwrap().name, "fix the flaky test");
+        assert!(matches!(inbox.discard(5), Err(InboxError::NotFound(5))));
+
+        let inbox = Inbox::load(inbox.path()).unwrap();
+        let texts: Vec<&str> = inbox.captures().iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["call the bank"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+//! Logic shared by the pomodoro frontends.
+use std::path::PathBuf;
+
+pub mod calendar;
+pub mod config;
+pub mod duration;
+pub mod estimates;
+pub mod export;
+pub mod flowtime;
+pub mod heatmap;
+pub mod history;
+pub mod ics;
+pub mod import;
+pub mod inbox;
+pub mod plan;
+pub mod reminder;
+pub mod schedule;
+pub mod stats;
+pub mod tasks;
+
+// environment variable used to override the data directory
+pub const DATA_DIR_ENV: &str = "POMODORO_DATA_DIR";
+
+/// Directory holding the recorded data, `POMODORO_DATA_DIR` wins over
+/// `$XDG_DATA_HOME/pomodoro`.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::data_dir().map(|dir| dir.join("pomodoro"))
+}
+
+/// Returns `false` when the terminal or the locale can't show unicode.
+///
+/// The linux console and serial terminals lack the emoji glyphs, a non UTF-8
+/// locale garbles every multi-byte character.
+pub fn supports_unicode() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    if matches!(term.as_str(), "linux" | "dumb" | "vt100" | "vt220" | "ansi") {
+        return false;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => true,
+    }
+}
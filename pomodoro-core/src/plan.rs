@@ -0,0 +1,359 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::TimerConfig;
+use crate::ics::Events;
+use crate::schedule::{Schedule, Step, StepKind};
+
+/// Work day of `pomodoro plan`, in `[plan]`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanConfig {
+    /// Start of the work day, like `"09:00"`.
+    #[serde(with = "opt_time", skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveTime>,
+    /// End of the work day, like `"17:30"`.
+    #[serde(with = "opt_time", skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveTime>,
+    /// Lunch break, like `"12:30-13:30"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lunch: Option<Block>,
+    /// Fixed blocks without pomodoros, like `"10:00-10:30 standup"`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<Block>,
+    /// iCalendar file, or directory of them, whose events are busy blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<PathBuf>,
+    /// Start each pomodoro at its planned time in the tui, `plan --start`
+    /// always does.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_start: Option<bool>,
+}
+
+impl PlanConfig {
+    pub fn is_unset(&self) -> bool {
+        *self == PlanConfig::default()
+    }
+
+    /// The lunch and the fixed blocks, the lunch named `lunch` unless it has a name.
+    pub fn busy(&self) -> Vec<Block> {
+        let lunch = self.lunch.iter().map(|lunch| Block {
+            name: lunch.name.clone().or_else(|| Some(String::from("lunch"))),
+            ..lunch.clone()
+        });
+        lunch.chain(self.blocks.iter().cloned()).collect()
+    }
+
+    /// The events of `date` in the calendar, none without a calendar.
+    pub fn meetings(&self, date: NaiveDate) -> io::Result<Vec<Block>> {
+        match &self.calendar {
+            Some(path) => Ok(Events::read(path)?.busy(date)),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Errors returned while reading a time or a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    InvalidTime(String),
+    /// Not `start-end name`, or ending before it starts.
+    InvalidBlock(String),
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::InvalidTime(text) => write!(f, "'{}' is not a time like 09:30", text),
+            PlanError::InvalidBlock(text) => {
+                write!(f, "'{}' is not a block like 12:30-13:30 lunch", text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// Parses a time of the day like `09:30` or `9:30`.
+pub fn parse_time(text: &str) -> Result<NaiveTime, PlanError> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .map_err(|_| PlanError::InvalidTime(text.to_string()))
+}
+
+pub fn format_time(time: NaiveTime) -> String {
+    time.format("%H:%M").to_string()
+}
+
+/// Time without pomodoros, like the lunch or a meeting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub name: Option<String>,
+}
+
+impl FromStr for Block {
+    type Err = PlanError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || PlanError::InvalidBlock(text.to_string());
+        let text = text.trim();
+        let (times, name) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let (start, end) = times.split_once('-').ok_or_else(invalid)?;
+        let (start, end) = (
+            parse_time(start).map_err(|_| invalid())?,
+            parse_time(end).map_err(|_| invalid())?,
+        );
+        if end <= start {
+            return Err(invalid());
+        }
+        let name = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+        Ok(Block { start, end, name })
+    }
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", format_time(self.start), format_time(self.end))?;
+        match &self.name {
+            Some(name) => write!(f, " {}", name),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Serialize for Block {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Block {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// What a slot of the timetable is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotKind {
+    Step(StepKind),
+    /// A lunch or fixed block, with its name.
+    Busy(Option<String>),
+}
+
+/// A line of the timetable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slot {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub kind: SlotKind,
+}
+
+impl Slot {
+    /// The step to run in this slot, `None` for a busy block.
+    pub fn step(&self) -> Option<Step> {
+        match self.kind {
+            SlotKind::Step(kind) => Some(Step {
+                kind,
+                length: (self.end - self.start).to_std().unwrap_or_default(),
+            }),
+            SlotKind::Busy(_) => None,
+        }
+    }
+
+    fn busy(block: &Block) -> Slot {
+        Slot {
+            start: block.start,
+            end: block.end,
+            kind: SlotKind::Busy(block.name.clone()),
+        }
+    }
+}
+
+/// Steps repeated through the day: the schedule of `timer`, or pomodoros of
+/// `focus` with their breaks up to the long one.
+pub fn day_steps(timer: &TimerConfig, focus: Duration, short: Duration) -> Vec<Step> {
+    match &timer.schedule {
+        Some(schedule) => schedule.steps().to_vec(),
+        None => {
+            let cycle = match (timer.long_break_duration, timer.long_break_every) {
+                (Some(_), Some(every)) if every > 0 => every,
+                _ => 1,
+            };
+            Schedule::classic(cycle, focus, short, timer)
+                .steps()
+                .to_vec()
+        }
+    }
+}
+
+/// Places `steps`, over and over, between `from` and `until`.
+///
+/// A pomodoro never overlaps a busy block, it's moved after it; a break is
+/// cut short by one. The day never ends with a break.
+pub fn plan(from: NaiveTime, until: NaiveTime, steps: &[Step], busy: &[Block]) -> Vec<Slot> {
+    let mut blocks: Vec<&Block> = busy
+        .iter()
+        .filter(|block| block.end > from && block.start < until)
+        .collect();
+    blocks.sort_by_key(|block| block.start);
+    let mut blocks = blocks.into_iter().peekable();
+    let mut slots = Vec::new();
+    let mut at = from;
+    let mut next = 0;
+    while !steps.is_empty() {
+        // blocks reached, the time goes on after them
+        while let Some(block) = blocks.next_if(|block| block.start <= at) {
+            slots.push(Slot::busy(block));
+            at = at.max(block.end);
+        }
+        let step = steps[next % steps.len()];
+        let length = chrono::Duration::from_std(step.length).unwrap_or(chrono::Duration::MAX);
+        let (mut end, days) = at.overflowing_add_signed(length);
+        if days != 0 || end > until {
+            break;
+        }
+        if let Some(block) = blocks.peek().filter(|block| block.start < end) {
+            if step.kind == StepKind::Focus {
+                // try again after the block
+                at = block.start;
+                continue;
+            }
+            end = block.start;
+        }
+        slots.push(Slot {
+            start: at,
+            end,
+            kind: SlotKind::Step(step.kind),
+        });
+        at = end;
+        next += 1;
+    }
+    while slots
+        .last()
+        .is_some_and(|slot| slot.step().is_some_and(|step| step.kind != StepKind::Focus))
+    {
+        slots.pop();
+    }
+    slots.extend(blocks.map(Slot::busy));
+    slots
+}
+
+/// The first of `busy` a phase of `length` starting `at` would run into.
+pub fn overlap(busy: &[Block], at: NaiveTime, length: Duration) -> Option<&Block> {
+    let length = chrono::Duration::from_std(length).unwrap_or(chrono::Duration::MAX);
+    let (end, days) = at.overflowing_add_signed(length);
+    busy.iter()
+        .filter(|block| block.end > at && (days != 0 || block.start < end))
+        .min_by_key(|block| block.start)
+}
+
+pub(crate) mod opt_time {
+    use chrono::NaiveTime;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<NaiveTime>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(time) => s.serialize_str(&super::format_time(*time)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<NaiveTime>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|text| super::parse_time(&text).map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(text: &str) -> NaiveTime {
+        parse_time(text).unwrap()
+    }
+
+    fn timetable(slots: &[Slot]) -> Vec<String> {
+        slots
+            .iter()
+            .map(|slot| {
+                let what = match &slot.kind {
+                    SlotKind::Step(kind) => kind.name(),
+                    SlotKind::Busy(name) => name.as_deref().unwrap_or("busy"),
+                };
+                format!("{} {}", format_time(slot.start), what)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_block() {
+        let block: Block = "12:30-13:30 lunch".parse().unwrap();
+        assert_eq!(block.start, time("12:30"));
+        assert_eq!(block.name.as_deref(), Some("lunch"));
+        assert_eq!(block.to_string(), "12:30-13:30 lunch");
+        assert_eq!("9:00-9:15".parse::<Block>().unwrap().name, None);
+        assert!("13:30-12:30".parse::<Block>().is_err());
+        assert!("noon".parse::<Block>().is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let minutes = |n: u64| Duration::from_secs(n * 60);
+        let steps = day_steps(&TimerConfig::default(), minutes(25), minutes(5));
+        let busy: Vec<Block> = vec!["10:15-10:30 standup".parse().unwrap()];
+        let slots = plan(time("09:00"), time("11:30"), &steps, &busy);
+        assert_eq!(
+            timetable(&slots),
+            vec![
+                "09:00 focus",
+                "09:25 break",
+                "09:30 focus",
+                "09:55 break",
+                // the pomodoro doesn't fit before the standup
+                "10:15 standup",
+                "10:30 focus",
+                "10:55 break",
+                "11:00 focus",
+            ]
+        );
+        assert_eq!(slots[3].end, time("10:00"));
+
+        let meeting = overlap(&busy, time("09:55"), minutes(25));
+        assert_eq!(meeting.map(|block| block.start), Some(time("10:15")));
+        assert_eq!(overlap(&busy, time("10:30"), minutes(25)), None);
+    }
+
+    #[test]
+    fn test_plan_cuts_breaks() {
+        let minutes = |n: u64| Duration::from_secs(n * 60);
+        let steps = [
+            Step {
+                kind: StepKind::Focus,
+                length: minutes(50),
+            },
+            Step {
+                kind: StepKind::Break,
+                length: minutes(10),
+            },
+        ];
+        let config = PlanConfig {
+            lunch: Some("12:55-13:30".parse().unwrap()),
+            ..Default::default()
+        };
+        let slots = plan(time("12:00"), time("14:00"), &steps, &config.busy());
+        assert_eq!(
+            timetable(&slots),
+            vec!["12:00 focus", "12:50 break", "12:55 lunch"]
+        );
+        assert_eq!(slots[1].end, time("12:55"));
+    }
+}
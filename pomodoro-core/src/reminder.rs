@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::plan::PlanConfig;
+
+const DEFAULT_ESCALATE_AFTER: u32 = 3;
+const DEFAULT_SNOOZE: Duration = Duration::from_secs(60 * 60);
+
+/// Reminders to start a pomodoro when the timer sits idle, in `[reminder]`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderConfig {
+    /// Idle time between two reminders, no reminders if unset.
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub every: Option<Duration>,
+    /// Start of the work hours, the one of `[plan]` if unset.
+    #[serde(
+        with = "crate::plan::opt_time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub from: Option<NaiveTime>,
+    /// End of the work hours, the one of `[plan]` if unset.
+    #[serde(
+        with = "crate::plan::opt_time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub until: Option<NaiveTime>,
+    /// Reminders sent before they become urgent, 3 if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalate_after: Option<u32>,
+    /// How long a snooze silences the reminders, 1h if unset.
+    #[serde(
+        with = "crate::duration::opt_text",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub snooze: Option<Duration>,
+}
+
+impl ReminderConfig {
+    pub fn is_unset(&self) -> bool {
+        *self == ReminderConfig::default()
+    }
+
+    /// Values of `self`, the unset work hours taken from the work day of `plan`.
+    pub fn or_plan(&self, plan: &PlanConfig) -> ReminderConfig {
+        ReminderConfig {
+            from: self.from.or(plan.from),
+            until: self.until.or(plan.until),
+            ..self.clone()
+        }
+    }
+
+    pub fn snooze(&self) -> Duration {
+        self.snooze.unwrap_or(DEFAULT_SNOOZE)
+    }
+
+    // work hours ending before they start go past midnight
+    fn in_hours(&self, time: NaiveTime) -> bool {
+        match (self.from, self.until) {
+            (Some(from), Some(until)) if from <= until => from <= time && time < until,
+            (Some(from), Some(until)) => from <= time || time < until,
+            (Some(from), None) => from <= time,
+            (None, Some(until)) => time < until,
+            (None, None) => true,
+        }
+    }
+}
+
+/// A reminder to send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nag {
+    /// Time idle so far, within the work hours.
+    pub idle: Duration,
+    /// Number of the reminder since the timer went idle, from 1.
+    pub count: u32,
+    /// Sent once the reminders were ignored `escalate_after` times.
+    pub urgent: bool,
+}
+
+/// Decides when to remind the user to start a pomodoro.
+///
+/// The idle time only counts within the work hours, so the first reminder of
+/// the day comes `every` after they start.
+#[derive(Debug, Clone, Default)]
+pub struct Reminders {
+    config: ReminderConfig,
+    idle_since: Option<DateTime<Local>>,
+    last: Option<DateTime<Local>>,
+    sent: u32,
+    snoozed_until: Option<DateTime<Local>>,
+}
+
+impl Reminders {
+    pub fn new(config: ReminderConfig) -> Self {
+        Reminders {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &ReminderConfig {
+        &self.config
+    }
+
+    /// Follows the timer, `idle` when no phase is running or about to start.
+    /// Returns the reminder to send now, if any.
+    pub fn check(&mut self, idle: bool, now: DateTime<Local>) -> Option<Nag> {
+        let every = self.config.every.filter(|every| !every.is_zero());
+        let Some(every) = every.filter(|_| idle && self.config.in_hours(now.time())) else {
+            self.idle_since = None;
+            self.last = None;
+            self.sent = 0;
+            return None;
+        };
+        let since = *self.idle_since.get_or_insert(now);
+        if self.snoozed_until(now).is_some() {
+            return None;
+        }
+        let waited = (now - self.last.unwrap_or(since))
+            .to_std()
+            .unwrap_or_default();
+        if waited < every {
+            return None;
+        }
+        self.last = Some(now);
+        self.sent += 1;
+        let escalate_after = self.config.escalate_after.unwrap_or(DEFAULT_ESCALATE_AFTER);
+        Some(Nag {
+            idle: (now - since).to_std().unwrap_or_default(),
+            count: self.sent,
+            urgent: self.sent > escalate_after,
+        })
+    }
+
+    /// Silences the reminders for the `snooze` time, returns when they resume.
+    pub fn snooze(&mut self, now: DateTime<Local>) -> DateTime<Local> {
+        let snooze = chrono::Duration::from_std(self.config.snooze()).unwrap_or_default();
+        let until = now + snooze;
+        self.snoozed_until = Some(until);
+        // the next reminder waits a whole `every` after the snooze
+        self.last = Some(until);
+        until
+    }
+
+    pub fn snoozed_until(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.snoozed_until.filter(|until| now < *until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 10, 19, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn reminders() -> Reminders {
+        Reminders::new(ReminderConfig {
+            every: Some(Duration::from_secs(15 * 60)),
+            from: NaiveTime::from_hms_opt(9, 0, 0),
+            until: NaiveTime::from_hms_opt(17, 0, 0),
+            escalate_after: Some(1),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_nag() {
+        let mut reminders = reminders();
+        // outside of the work hours
+        assert_eq!(reminders.check(true, at(8, 0)), None);
+        assert_eq!(reminders.check(true, at(9, 0)), None);
+        assert_eq!(reminders.check(true, at(9, 14)), None);
+        let nag = reminders.check(true, at(9, 15)).unwrap();
+        assert_eq!((nag.count, nag.urgent), (1, false));
+        assert_eq!(reminders.check(true, at(9, 16)), None);
+        let nag = reminders.check(true, at(9, 30)).unwrap();
+        assert_eq!(nag.idle, Duration::from_secs(30 * 60));
+        assert_eq!((nag.count, nag.urgent), (2, true));
+
+        // a pomodoro starts the count over
+        assert_eq!(reminders.check(false, at(9, 31)), None);
+        assert_eq!(reminders.check(true, at(10, 0)), None);
+        assert_eq!(reminders.check(true, at(10, 15)).unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_snooze() {
+        let mut reminders = reminders();
+        reminders.check(true, at(9, 0));
+        assert_eq!(reminders.snooze(at(9, 10)), at(10, 10));
+        assert_eq!(reminders.check(true, at(9, 30)), None);
+        assert_eq!(reminders.check(true, at(10, 10)), None);
+        assert_eq!(reminders.check(true, at(10, 25)).unwrap().count, 1);
+        assert_eq!(reminders.snoozed_until(at(10, 25)), None);
+    }
+
+    #[test]
+    fn test_night_hours() {
+        let config = ReminderConfig {
+            from: NaiveTime::from_hms_opt(22, 0, 0),
+            until: NaiveTime::from_hms_opt(6, 0, 0),
+            ..Default::default()
+        };
+        assert!(config.in_hours(NaiveTime::from_hms_opt(23, 0, 0).unwrap()));
+        assert!(config.in_hours(NaiveTime::from_hms_opt(2, 0, 0).unwrap()));
+        assert!(!config.in_hours(NaiveTime::from_hms_opt(12, 0, 0).unwrap()));
+    }
+}
@@ -0,0 +1,403 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::TimerConfig;
+use crate::duration::parse_duration;
+use crate::history::Phase;
+
+// more steps than this is surely a mistake, like `(25f 5b)x100000`
+const MAX_STEPS: usize = 1000;
+
+/// What a step of a schedule is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepKind {
+    Focus,
+    Break,
+    LongBreak,
+}
+
+impl StepKind {
+    // names accepted in a schedule, the first one is used to print it
+    const NAMES: [(StepKind, &'static [&'static str]); 3] = [
+        (StepKind::Focus, &["focus", "pomodoro", "f", "p"]),
+        (StepKind::Break, &["break", "b"]),
+        (
+            StepKind::LongBreak,
+            &["long-break", "long_break", "lb", "l"],
+        ),
+    ];
+
+    /// Phase recorded in the history for this step.
+    pub fn phase(self) -> Phase {
+        match self {
+            StepKind::Focus => Phase::Pomodoro,
+            StepKind::Break | StepKind::LongBreak => Phase::Break,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StepKind::Focus => "focus",
+            StepKind::Break => "break",
+            StepKind::LongBreak => "long-break",
+        }
+    }
+
+    fn parse(name: &str) -> Option<StepKind> {
+        let name = name.to_ascii_lowercase();
+        StepKind::NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&name.as_str()))
+            .map(|(kind, _)| *kind)
+    }
+}
+
+/// One phase of a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub kind: StepKind,
+    pub length: Duration,
+}
+
+/// Errors returned while reading a schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    Empty,
+    /// A word that is neither a step nor a length.
+    InvalidStep(String),
+    UnknownKind(String),
+    /// A length at the end, without its kind.
+    MissingKind(String),
+    InvalidRepeat(String),
+    Unbalanced,
+    /// Parentheses without a step, like `()x3`.
+    EmptyGroup,
+    TooLong,
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Empty => write!(f, "empty schedule"),
+            ScheduleError::InvalidStep(word) => write!(f, "'{}' is not a step like 25f", word),
+            ScheduleError::UnknownKind(word) => {
+                write!(f, "unknown step '{}', use focus, break or long-break", word)
+            }
+            ScheduleError::MissingKind(word) => write!(f, "'{}' needs a kind, like focus", word),
+            ScheduleError::InvalidRepeat(word) => write!(f, "'{}' is not a repeat like x3", word),
+            ScheduleError::Unbalanced => write!(f, "unbalanced parentheses"),
+            ScheduleError::EmptyGroup => write!(f, "empty parentheses"),
+            ScheduleError::TooLong => write!(f, "more than {} steps", MAX_STEPS),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// A custom sequence of phases, like `50 focus, 10 break` or `(25f 5b)x3 25f 20lb`.
+///
+/// Steps are a length (`25`, `25m`, `90s`) and a kind (`focus`/`f`,
+/// `break`/`b`, `long-break`/`lb`), glued or not, separated by commas or
+/// spaces. A group in parentheses followed by `xN` is repeated N times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    // the text it was read from, kept to write it back as typed
+    source: String,
+    steps: Vec<Step>,
+}
+
+impl Schedule {
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// The usual alternation: `pomodoros` times `focus` and a break, long
+    /// ones as set in `timer`.
+    pub fn classic(pomodoros: u32, focus: Duration, short: Duration, timer: &TimerConfig) -> Self {
+        let mut steps = Vec::new();
+        for completed in 1..=pomodoros {
+            let length = timer.break_after(completed, short);
+            let kind = if length == short {
+                StepKind::Break
+            } else {
+                StepKind::LongBreak
+            };
+            steps.push(Step {
+                kind: StepKind::Focus,
+                length: focus,
+            });
+            steps.push(Step { kind, length });
+        }
+        let source = steps
+            .iter()
+            .map(|step| {
+                format!(
+                    "{} {}",
+                    crate::duration::format_duration(step.length),
+                    step.kind.name()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Schedule { source, steps }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+
+fn tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if c == '(' || c == ')' || c == ',' || c.is_whitespace() {
+            if !word.is_empty() {
+                tokens.push(Token::Word(std::mem::take(&mut word)));
+            }
+            match c {
+                '(' => tokens.push(Token::Open),
+                ')' => tokens.push(Token::Close),
+                _ => {}
+            }
+        } else {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    // steps until the end of the text, or of the group when `nested`
+    fn sequence(&mut self, nested: bool) -> Result<Vec<Step>, ScheduleError> {
+        let mut steps = Vec::new();
+        loop {
+            match self.next() {
+                None if nested => return Err(ScheduleError::Unbalanced),
+                None => return Ok(steps),
+                Some(Token::Close) if nested => return Ok(steps),
+                Some(Token::Close) => return Err(ScheduleError::Unbalanced),
+                Some(Token::Open) => {
+                    let group = self.sequence(true)?;
+                    if group.is_empty() {
+                        return Err(ScheduleError::EmptyGroup);
+                    }
+                    let count = self.repeat()?;
+                    if group.len().saturating_mul(count) + steps.len() > MAX_STEPS {
+                        return Err(ScheduleError::TooLong);
+                    }
+                    for _ in 0..count {
+                        steps.extend_from_slice(&group);
+                    }
+                }
+                Some(Token::Word(word)) => steps.push(self.step(&word)?),
+            }
+            if steps.len() > MAX_STEPS {
+                return Err(ScheduleError::TooLong);
+            }
+        }
+    }
+
+    // `x3` after a group, once without it
+    fn repeat(&mut self) -> Result<usize, ScheduleError> {
+        let Some(Token::Word(word)) = self.peek().cloned() else {
+            return Ok(1);
+        };
+        let Some(count) = word.strip_prefix(['x', 'X', '*', '×']) else {
+            return Ok(1);
+        };
+        self.pos += 1;
+        let count = match count {
+            // `x 3`
+            "" => match self.next() {
+                Some(Token::Word(count)) => count,
+                _ => return Err(ScheduleError::InvalidRepeat(word)),
+            },
+            count => count.to_string(),
+        };
+        match count.parse() {
+            Ok(count) if count > 0 => Ok(count),
+            _ => Err(ScheduleError::InvalidRepeat(count)),
+        }
+    }
+
+    // `25f`, or `25` followed by `focus`
+    fn step(&mut self, word: &str) -> Result<Step, ScheduleError> {
+        if let Some(step) = glued_step(word) {
+            return Ok(step);
+        }
+        let length = match parse_duration(word) {
+            Ok(length) if length > Duration::ZERO => length,
+            _ => return Err(ScheduleError::InvalidStep(word.to_string())),
+        };
+        match self.next() {
+            Some(Token::Word(kind)) => match StepKind::parse(&kind) {
+                Some(kind) => Ok(Step { kind, length }),
+                None => Err(ScheduleError::UnknownKind(kind)),
+            },
+            _ => Err(ScheduleError::MissingKind(word.to_string())),
+        }
+    }
+}
+
+fn glued_step(word: &str) -> Option<Step> {
+    let lower = word.to_ascii_lowercase();
+    let mut names: Vec<(StepKind, &str)> = StepKind::NAMES
+        .iter()
+        .flat_map(|(kind, names)| names.iter().map(move |name| (*kind, *name)))
+        .collect();
+    // `5lb` is a long break, not `5l` and a `b`
+    names.sort_by_key(|(_, name)| std::cmp::Reverse(name.len()));
+    names.into_iter().find_map(|(kind, name)| {
+        let length = lower
+            .strip_suffix(name)
+            .filter(|length| !length.is_empty())?;
+        parse_duration(length)
+            .ok()
+            .filter(|length| *length > Duration::ZERO)
+            .map(|length| Step { kind, length })
+    })
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokens(text),
+            pos: 0,
+        };
+        let steps = parser.sequence(false)?;
+        if steps.is_empty() {
+            return Err(ScheduleError::Empty);
+        }
+        Ok(Schedule {
+            source: text.trim().to_string(),
+            steps,
+        })
+    }
+}
+
+impl Serialize for Schedule {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    fn step(kind: StepKind, n: u64) -> Step {
+        Step {
+            kind,
+            length: minutes(n),
+        }
+    }
+
+    #[test]
+    fn test_words() {
+        let schedule: Schedule = "50 focus, 10 break, 25 focus, 30 long-break"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            schedule.steps(),
+            &[
+                step(StepKind::Focus, 50),
+                step(StepKind::Break, 10),
+                step(StepKind::Focus, 25),
+                step(StepKind::LongBreak, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        let schedule: Schedule = "(25f 5b)x3 25f 20lb".parse().unwrap();
+        assert_eq!(schedule.steps().len(), 8);
+        assert_eq!(schedule.steps()[5], step(StepKind::Break, 5));
+        assert_eq!(schedule.steps()[7], step(StepKind::LongBreak, 20));
+        assert_eq!(schedule.to_string(), "(25f 5b)x3 25f 20lb");
+
+        let nested: Schedule = "((1h30mf 90sb) x 2 45s break)*2".parse().unwrap();
+        assert_eq!(nested.steps().len(), 10);
+        assert_eq!(nested.steps()[0].length, minutes(90));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| text.parse::<Schedule>().unwrap_err();
+        assert_eq!(error(" "), ScheduleError::Empty);
+        assert_eq!(error("f25"), ScheduleError::InvalidStep("f25".into()));
+        assert_eq!(error("25 nap"), ScheduleError::UnknownKind("nap".into()));
+        assert_eq!(error("25f 5"), ScheduleError::MissingKind("5".into()));
+        assert_eq!(error("(25f 5b"), ScheduleError::Unbalanced);
+        assert_eq!(error("25f)"), ScheduleError::Unbalanced);
+        assert_eq!(error("(25f)x0"), ScheduleError::InvalidRepeat("0".into()));
+        assert_eq!(error("(25f 5b)x1000"), ScheduleError::TooLong);
+        assert_eq!(
+            error("25f () x 18446744073709551615"),
+            ScheduleError::EmptyGroup
+        );
+        assert_eq!(error("(25f)x18446744073709551615"), ScheduleError::TooLong);
+    }
+
+    #[test]
+    fn test_classic() {
+        let timer = TimerConfig {
+            long_break_duration: Some(minutes(15)),
+            long_break_every: Some(2),
+            ..Default::default()
+        };
+        let schedule = Schedule::classic(2, minutes(25), minutes(5), &timer);
+        assert_eq!(
+            schedule.steps(),
+            &[
+                step(StepKind::Focus, 25),
+                step(StepKind::Break, 5),
+                step(StepKind::Focus, 25),
+                step(StepKind::LongBreak, 15),
+            ]
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use chrono::{Days, NaiveDate};
+
+use crate::calendar::Calendar;
+use crate::history::{InterruptionKind, Outcome, Phase, Record};
+
+/// Totals over a list of records.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Summary {
+    pub completed: usize,
+    pub interrupted: usize,
+    /// Abandoned pomodoros, neither completed nor interrupted.
+    pub voided: usize,
+    /// Pomodoros ended early to take the break.
+    pub skipped: usize,
+    /// Time spent in pomodoros, interrupted ones included, voided ones not.
+    pub focus: Duration,
+    /// Days with at least one pomodoro.
+    pub active_days: usize,
+    pub internal_interruptions: usize,
+    pub external_interruptions: usize,
+    /// Mean of the focus ratings, `None` if no pomodoro was rated.
+    pub focus_rating: Option<f64>,
+    /// Time spent in pomodoros past their planned end.
+    pub overtime: Duration,
+}
+
+impl Summary {
+    pub fn new(records: &[Record], calendar: &Calendar) -> Self {
+        let mut summary = Summary::default();
+        let mut days = BTreeSet::new();
+        for record in pomodoros(records) {
+            match record.outcome {
+                Outcome::Completed => summary.completed += 1,
+                Outcome::Interrupted => summary.interrupted += 1,
+                Outcome::Skipped => summary.skipped += 1,
+                // `pomodoros` skips them, the voided ones are counted below
+                Outcome::Voided | Outcome::Reset => {}
+            }
+            summary.focus += record.duration();
+            summary.overtime += record.overtime.unwrap_or_default();
+            summary.internal_interruptions += record.interruptions_of(InterruptionKind::Internal);
+            summary.external_interruptions += record.interruptions_of(InterruptionKind::External);
+            days.insert(calendar.day_of(&record.start));
+        }
+        summary.active_days = days.len();
+        let ratings: Vec<u8> = pomodoros(records)
+            .filter_map(|record| record.focus_rating)
+            .collect();
+        summary.focus_rating = (!ratings.is_empty())
+            .then(|| ratings.iter().map(|&r| f64::from(r)).sum::<f64>() / ratings.len() as f64);
+        summary.voided = records
+            .iter()
+            .filter(|record| record.kind == Phase::Pomodoro && record.outcome == Outcome::Voided)
+            .count();
+        summary
+    }
+
+    /// Completed pomodoros over the started ones, `None` without pomodoros.
+    pub fn completion_rate(&self) -> Option<f64> {
+        let started = self.completed + self.interrupted + self.skipped;
+        (started > 0).then(|| self.completed as f64 / started as f64)
+    }
+
+    pub fn average_focus(&self) -> Duration {
+        match self.active_days {
+            0 => Duration::ZERO,
+            days => self.focus / days as u32,
+        }
+    }
+
+    pub fn average_completed(&self) -> f64 {
+        match self.active_days {
+            0 => 0.0,
+            days => self.completed as f64 / days as f64,
+        }
+    }
+}
+
+/// Focus time of each of the `days` days ending with `last`, oldest first.
+pub fn focus_by_day(
+    records: &[Record],
+    calendar: &Calendar,
+    last: NaiveDate,
+    days: u64,
+) -> Vec<(NaiveDate, Duration)> {
+    let first = last - Days::new(days.saturating_sub(1));
+    let mut result: Vec<(NaiveDate, Duration)> = first
+        .iter_days()
+        .take(days as usize)
+        .map(|day| (day, Duration::ZERO))
+        .collect();
+    let by_day = by_day(calendar, pomodoros(records));
+    for (day, focus) in result.iter_mut() {
+        if let Some(records) = by_day.get(day) {
+            *focus = records.iter().map(|record| record.duration()).sum();
+        }
+    }
+    result
+}
+
+/// Completed pomodoros of every day with at least one.
+pub fn completed_by_day(records: &[Record], calendar: &Calendar) -> BTreeMap<NaiveDate, u64> {
+    count(by_day(calendar, completed(records)))
+}
+
+/// Completed pomodoros of every week with at least one, by monday.
+pub fn completed_by_week(records: &[Record], calendar: &Calendar) -> BTreeMap<NaiveDate, u64> {
+    count(by_week(calendar, completed(records)))
+}
+
+/// Completed pomodoros of every month with at least one, by first day.
+pub fn completed_by_month(records: &[Record], calendar: &Calendar) -> BTreeMap<NaiveDate, u64> {
+    count(by_month(calendar, completed(records)))
+}
+
+/// Groups `records` by the day they belong to.
+pub fn by_day<'a>(
+    calendar: &Calendar,
+    records: impl Iterator<Item = &'a Record>,
+) -> BTreeMap<NaiveDate, Vec<&'a Record>> {
+    group_by(records, |record| calendar.day_of(&record.start))
+}
+
+/// Groups `records` by week, the key is the monday.
+pub fn by_week<'a>(
+    calendar: &Calendar,
+    records: impl Iterator<Item = &'a Record>,
+) -> BTreeMap<NaiveDate, Vec<&'a Record>> {
+    group_by(records, |record| {
+        calendar.week_of(calendar.day_of(&record.start))
+    })
+}
+
+/// Groups `records` by month, the key is the first day of the month.
+pub fn by_month<'a>(
+    calendar: &Calendar,
+    records: impl Iterator<Item = &'a Record>,
+) -> BTreeMap<NaiveDate, Vec<&'a Record>> {
+    group_by(records, |record| {
+        calendar.month_of(calendar.day_of(&record.start))
+    })
+}
+
+/// Completed pomodoros started in each hour of `day`.
+pub fn completed_by_hour(records: &[Record], calendar: &Calendar, day: NaiveDate) -> [u64; 24] {
+    let mut hours = [0; 24];
+    for record in completed_on(records, calendar, day) {
+        hours[calendar.hour_of(&record.start) as usize] += 1;
+    }
+    hours
+}
+
+/// Completed pomodoros started on `day`.
+pub fn completed_on<'a>(
+    records: &'a [Record],
+    calendar: &'a Calendar,
+    day: NaiveDate,
+) -> impl Iterator<Item = &'a Record> {
+    completed(records).filter(move |record| calendar.day_of(&record.start) == day)
+}
+
+fn completed(records: &[Record]) -> impl Iterator<Item = &Record> {
+    pomodoros(records).filter(|record| record.outcome == Outcome::Completed)
+}
+
+// voided and reset pomodoros never count
+fn pomodoros(records: &[Record]) -> impl Iterator<Item = &Record> {
+    records.iter().filter(|record| {
+        record.kind == Phase::Pomodoro
+            && !matches!(record.outcome, Outcome::Voided | Outcome::Reset)
+    })
+}
+
+fn group_by<'a>(
+    records: impl Iterator<Item = &'a Record>,
+    key: impl Fn(&Record) -> NaiveDate,
+) -> BTreeMap<NaiveDate, Vec<&'a Record>> {
+    let mut groups: BTreeMap<NaiveDate, Vec<&Record>> = BTreeMap::new();
+    for record in records {
+        groups.entry(key(record)).or_default().push(record);
+    }
+    groups
+}
+
+fn count(groups: BTreeMap<NaiveDate, Vec<&Record>>) -> BTreeMap<NaiveDate, u64> {
+    groups
+        .into_iter()
+        .map(|(key, records)| (key, records.len() as u64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn record(day: u32, hour: u32, minutes: i64, outcome: Outcome) -> Record {
+        let start = Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+        Record::new(
+            Phase::Pomodoro,
+            start,
+            start + chrono::Duration::minutes(minutes),
+            outcome,
+        )
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut records = vec![
+            record(1, 9, 25, Outcome::Completed),
+            record(1, 10, 25, Outcome::Completed),
+            record(2, 9, 10, Outcome::Interrupted),
+            record(4, 9, 15, Outcome::Voided),
+            record(2, 10, 5, Outcome::Skipped),
+            record(2, 11, 7, Outcome::Reset),
+        ];
+        records[0].focus_rating = Some(4);
+        records[1].focus_rating = Some(3);
+        records[3].focus_rating = Some(1);
+        records[1].overtime = Some(Duration::from_secs(3 * 60));
+        let summary = Summary::new(&records, &Calendar::default());
+        assert_eq!(summary.completed, 2);
+        assert_eq!(summary.interrupted, 1);
+        assert_eq!(summary.voided, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.focus_rating, Some(3.5));
+        assert_eq!(summary.focus, Duration::from_secs(65 * 60));
+        assert_eq!(summary.overtime, Duration::from_secs(3 * 60));
+        assert_eq!(summary.active_days, 2);
+        assert_eq!(summary.average_focus(), Duration::from_secs(65 * 30));
+        assert_eq!(summary.completion_rate(), Some(2.0 / 4.0));
+        assert_eq!(
+            Summary::new(&[], &Calendar::default()).completion_rate(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_buckets() {
+        let records = vec![
+            record(1, 9, 25, Outcome::Completed),
+            record(3, 9, 25, Outcome::Completed),
+            record(3, 9, 25, Outcome::Completed),
+            record(3, 14, 5, Outcome::Interrupted),
+        ];
+        let last = NaiveDate::from_ymd_opt(2024, 3, 3).unwrap();
+        let calendar = Calendar::default();
+        let days = focus_by_day(&records, &calendar, last, 3);
+        let minutes: Vec<u64> = days.iter().map(|(_, d)| d.as_secs() / 60).collect();
+        assert_eq!(minutes, vec![25, 0, 55]);
+        assert_eq!(days[0].0, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+
+        let completed = completed_by_day(&records, &calendar);
+        assert_eq!(completed.len(), 2);
+        assert_eq!(completed[&last], 2);
+
+        let hours = completed_by_hour(&records, &calendar, last);
+        assert_eq!(hours[9], 2);
+        assert_eq!(hours[14], 0);
+
+        // 2024-03-01 is a friday, 2024-03-03 the sunday after
+        let weeks = completed_by_week(&records, &calendar);
+        assert_eq!(weeks[&NaiveDate::from_ymd_opt(2024, 2, 26).unwrap()], 3);
+        let months = completed_by_month(&records, &calendar);
+        assert_eq!(months[&NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()], 3);
+    }
+
+    #[test]
+    fn test_late_pomodoro_counts_for_the_day_before() {
+        let records = vec![record(3, 1, 25, Outcome::Completed)];
+        let calendar = Calendar::new(4, None).unwrap();
+        let completed = completed_by_day(&records, &calendar);
+        assert_eq!(
+            completed.keys().next(),
+            NaiveDate::from_ymd_opt(2024, 3, 2).as_ref()
+        );
+    }
+}
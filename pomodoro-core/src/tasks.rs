@@ -0,0 +1,190 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+const TASKS_FILE: &str = "tasks.json";
+
+/// Something to work on, one or more pomodoros long.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: u32,
+    pub name: String,
+    /// Pomodoros the task should take.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// Pomodoros completed working on the task.
+    #[serde(default)]
+    pub actual: u32,
+    pub created: DateTime<Local>,
+    /// When the task was marked done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done: Option<DateTime<Local>>,
+}
+
+impl Task {
+    pub fn is_done(&self) -> bool {
+        self.done.is_some()
+    }
+}
+
+/// Errors returned while reading or changing the task list.
+#[derive(Debug)]
+pub enum TaskError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    /// There is no data directory on this system.
+    NoPath,
+    NotFound(u32),
+    EmptyName,
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::Io(path, e) => write!(f, "cannot access {}: {}", path.display(), e),
+            TaskError::Parse(path, e) => write!(f, "invalid tasks {}: {}", path.display(), e),
+            TaskError::NoPath => write!(f, "no data directory, set {}", crate::DATA_DIR_ENV),
+            TaskError::NotFound(id) => write!(f, "no task {}", id),
+            TaskError::EmptyName => write!(f, "the task name is empty"),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// The tasks, saved as a json array after every change.
+#[derive(Debug, Clone)]
+pub struct TaskList {
+    path: PathBuf,
+    tasks: Vec<Task>,
+}
+
+impl TaskList {
+    /// Reads the tasks of `path`, a missing file is an empty list.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, TaskError> {
+        let mut list = TaskList {
+            path: path.into(),
+            tasks: Vec::new(),
+        };
+        list.reload()?;
+        Ok(list)
+    }
+
+    /// Task list in the data directory.
+    pub fn open() -> Result<Self, TaskError> {
+        let dir = crate::data_dir().ok_or(TaskError::NoPath)?;
+        TaskList::load(dir.join(TASKS_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every task, oldest first.
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    /// Tasks not done yet, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|task| !task.is_done())
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    /// Reads the file again, to see the changes of other shells.
+    pub fn reload(&mut self) -> Result<(), TaskError> {
+        self.tasks = match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| TaskError::Parse(self.path.clone(), e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(TaskError::Io(self.path.clone(), e)),
+        };
+        Ok(())
+    }
+
+    pub fn add(&mut self, name: &str, estimate: Option<u32>) -> Result<&Task, TaskError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(TaskError::EmptyName);
+        }
+        self.reload()?;
+        let id = self.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+        self.tasks.push(Task {
+            id,
+            name: name.to_string(),
+            estimate,
+            actual: 0,
+            created: Local::now(),
+            done: None,
+        });
+        self.save()?;
+        Ok(&self.tasks[self.tasks.len() - 1])
+    }
+
+    pub fn mark_done(&mut self, id: u32) -> Result<(), TaskError> {
+        self.reload()?;
+        self.get_mut(id)?.done = Some(Local::now());
+        self.save()
+    }
+
+    /// Counts a completed pomodoro for task `id`.
+    pub fn add_pomodoro(&mut self, id: u32) -> Result<(), TaskError> {
+        self.reload()?;
+        self.get_mut(id)?.actual += 1;
+        self.save()
+    }
+
+    fn get_mut(&mut self, id: u32) -> Result<&mut Task, TaskError> {
+        self.tasks
+            .iter_mut()
+            .find(|task| task.id == id)
+            .ok_or(TaskError::NotFound(id))
+    }
+
+    fn save(&self) -> Result<(), TaskError> {
+        let io_err = |e| TaskError::Io(self.path.clone(), e);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        let content =
+            serde_json::to_string_pretty(&self.tasks).expect("tasks are always valid json");
+        fs::write(&self.path, content).map_err(io_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tasks() {
+        let path = std::env::temp_dir().join("pomodoro-core-tasks-test.json");
+        let _ = fs::remove_file(&path);
+        let mut list = TaskList::load(&path).unwrap();
+        assert!(matches!(list.add("  ", None), Err(TaskError::EmptyName)));
+        let docs = list.add("Write the docs", Some(3)).unwrap().id;
+        let review = list.add("Review", None).unwrap().id;
+        // a change made meanwhile by another shell is kept
+        TaskList::load(&path).unwrap().add("Deploy", None).unwrap();
+        list.add_pomodoro(docs).unwrap();
+        list.add_pomodoro(docs).unwrap();
+        list.mark_done(review).unwrap();
+        assert!(matches!(list.mark_done(42), Err(TaskError::NotFound(42))));
+
+        let list = TaskList::load(&path).unwrap();
+        let pending: Vec<&Task> = list.pending().collect();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].name, "Write the docs");
+        assert_eq!(pending[1].name, "Deploy");
+        assert_eq!((pending[0].estimate, pending[0].actual), (Some(3), 2));
+        assert!(list.get(review).unwrap().is_done());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,6 +1,8 @@
 use std::time::Instant;
 
-#[derive(Debug, Clone, Copy)]
+use pomodoro_core::history::InterruptionKind;
+
+#[derive(Debug, Clone)]
 pub enum Message {
     IncrementPomodoroCounter,
     DecrementPomodoroCounter,
@@ -10,5 +12,23 @@ pub enum Message {
     DecrementBreakDuration,
     StartPressed,
     StopPressed,
+    VoidPressed,
+    SkipPressed,
+    ResetPressed,
+    // add some minutes to the phase in progress
+    Extend(u64),
     Tick(Instant),
+    NoteChanged(String),
+    Mark(InterruptionKind),
+    IntentionChanged(String),
+    ReflectionChanged(String),
+    // focus rating of the finished pomodoro, `None` to skip it
+    Rate(Option<u8>),
+    ProfileSelected(String),
+    // keep the next phase from starting by itself
+    CancelAutoStart,
+    // end the phase counting overtime
+    MoveOn,
+    // the window is closing, save what is left
+    CloseRequested,
 }
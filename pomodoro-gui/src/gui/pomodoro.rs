@@ -1,20 +1,18 @@
 use crate::gui::message::Message;
 use iced::executor;
 use iced::theme::{Theme};
-use iced::widget::{button, column, row, text};
-use iced::{Application, Command, Element, Subscription};
+use iced::widget::{button, column, pick_list, row, text, text_input};
+use iced::{keyboard, subscription, window, Application, Color, Command, Element, Event, Subscription};
 use std::time::{Duration, Instant};
 
-// https://github.com/iced-rs/iced/blob/0.10/examples/stopwatch/src/main.rs
-struct Stopwatch {
-    duration: Duration,
-    state: State,
-}
-
-enum State {
-    Idle,
-    Ticking { last_tick: Instant },
-}
+use chrono::{DateTime, Local};
+use pomodoro_core::config::{Config, PromptsConfig, TimerConfig};
+use pomodoro_core::duration::format_duration;
+use pomodoro_core::flowtime::Stopwatch;
+use pomodoro_core::history::{
+    History, Interruption, InterruptionKind, Outcome, Phase, Record, Running,
+};
+use pomodoro_core::schedule::{Step, StepKind};
 
 pub struct Pomodoro {
     // pomodoro duration in minutes
@@ -23,6 +21,19 @@ pub struct Pomodoro {
     pomodoro_counter: u32,
     // pomodoro break duration in minutes
     break_duration: u64,
+    // length of the next break, long every few pomodoros or a part of the flowtime focus
+    break_length: Duration,
+    // pomodoros completed since the start, for the long breaks
+    completed: u32,
+
+    // profiles of the config file
+    config: Config,
+    // name of the picked profile
+    profile: Option<String>,
+    // timer settings of the config or of the picked profile
+    timer: TimerConfig,
+    // index of the current step when the timer has a schedule
+    step: usize,
 
     // timer
     elapsed_time: Stopwatch,
@@ -31,6 +42,313 @@ pub struct Pomodoro {
 
     // string to print in the gui
     str_pomodoro: String,
+
+    // where the pomodoros and their interruptions are saved
+    history: Option<History>,
+    // start of the pomodoro in progress
+    pomodoro_start: Option<DateTime<Local>>,
+    // interruptions of the pomodoro in progress
+    marks: Vec<Interruption>,
+    marks_checked: Option<Instant>,
+    // note of the next interruption
+    note: String,
+    // when the pomodoro in progress was stopped
+    paused_at: Option<Instant>,
+    // pause after which the pomodoro is voided, never if `None`
+    void_after: Option<Duration>,
+    // when the next phase starts by itself, until cancelled
+    auto_start: Option<Instant>,
+    // when the phase in progress reached its planned end, counting overtime
+    overtime: Option<Instant>,
+    // time added to the phase in progress
+    extended: Duration,
+
+    // questions asked around each pomodoro
+    prompts: PromptsConfig,
+    // what the pomodoro is for
+    intention: String,
+    // what got done, typed at the end
+    reflection: String,
+    // finished pomodoro waiting for its reflection to be saved
+    pending: Option<Record>,
+    // why the last pomodoro couldn't be saved, until one is
+    save_error: Option<String>,
+}
+
+impl Pomodoro {
+    fn count_marks(&self, kind: InterruptionKind) -> usize {
+        self.marks.iter().filter(|mark| mark.kind == kind).count()
+    }
+
+    fn mark(&mut self, kind: InterruptionKind) {
+        if self.pomodoro_start.is_none() {
+            return;
+        }
+        let note = Some(self.note.trim().to_string()).filter(|note| !note.is_empty());
+        let interruption = Interruption::now(kind, note);
+        if let Some(history) = &self.history {
+            if let Err(e) = history.mark(&interruption) {
+                self.str_pomodoro = e.to_string();
+            }
+        }
+        self.marks.push(interruption);
+        self.note.clear();
+    }
+
+    // marks can be added from other shells with `pomodoro mark`
+    fn refresh_marks(&mut self) {
+        if self.marks_checked.map_or(false, |checked| checked.elapsed().as_secs() < 1) {
+            return;
+        }
+        self.marks_checked = Some(Instant::now());
+        if let (Some(history), Some(start)) = (&self.history, self.pomodoro_start) {
+            if let Ok(marks) = history.marks_since(start) {
+                self.marks = marks;
+            }
+        }
+    }
+
+    fn start_pomodoro(&mut self) {
+        if self.pomodoro_start.is_some() {
+            return;
+        }
+        let running = Running {
+            kind: Phase::Pomodoro,
+            start: Local::now(),
+        };
+        if let Some(history) = &self.history {
+            let _ = history.set_running(Some(&running));
+        }
+        self.pomodoro_start = Some(running.start);
+        self.marks.clear();
+    }
+
+    // abandon the pomodoro in progress, it doesn't count
+    fn void_pomodoro(&mut self) {
+        if self.pomodoro_start.is_none() {
+            return;
+        }
+        self.finish_pomodoro(Outcome::Voided);
+        self.elapsed_time = Stopwatch::default();
+        self.paused_at = None;
+        self.str_pomodoro = "Pomodoro voided, start a new one".to_string();
+    }
+
+    fn finish_pomodoro(&mut self, outcome: Outcome) {
+        let Some(start) = self.pomodoro_start.take() else {
+            return;
+        };
+        let mut record = Record::new(Phase::Pomodoro, start, Local::now(), outcome);
+        record.interruptions = std::mem::take(&mut self.marks);
+        record.overtime = self.overtime.take().map(|at| at.elapsed());
+        record.extended = Some(std::mem::take(&mut self.extended)).filter(|d| !d.is_zero());
+        if self.timer.is_flowtime() {
+            record.tags.push(String::from("flowtime"));
+        }
+        let intention = std::mem::take(&mut self.intention);
+        record.intention = Some(intention.trim().to_string()).filter(|t| !t.is_empty());
+        if let Some(history) = &self.history {
+            if let Ok(marks) = history.take_marks(start) {
+                record.interruptions = marks;
+            }
+            let _ = history.set_running(None);
+        }
+        // a reflection still unanswered is saved without a rating
+        self.rate(None);
+        // no reflection while an interruption note is typed
+        if outcome == Outcome::Completed && self.prompts.reflection && self.note.trim().is_empty() {
+            self.pending = Some(record);
+        } else {
+            self.save_record(&record);
+        }
+    }
+
+    fn current_step(&self) -> Option<Step> {
+        self.timer.schedule.as_ref()?.steps().get(self.step).copied()
+    }
+
+    // the schedule is over, waiting to run again
+    fn schedule_done(&self) -> bool {
+        self.timer.schedule.is_some() && self.current_step().is_none()
+    }
+
+    fn next_step(&mut self) {
+        self.step += 1;
+        self.load_step();
+    }
+
+    // take the phase of the current step, the pomodoros left are the focus
+    // steps left
+    fn load_step(&mut self) {
+        let Some(schedule) = &self.timer.schedule else {
+            return;
+        };
+        let steps = schedule.steps().get(self.step..).unwrap_or_default();
+        self.pomodoro_counter = steps.iter().filter(|step| step.kind == StepKind::Focus).count() as u32;
+        let Some(step) = steps.first().copied() else {
+            self.str_pomodoro = "Schedule done, press Start to run it again".to_string();
+            return;
+        };
+        self.is_pomodoro = step.kind == StepKind::Focus;
+        if !self.is_pomodoro {
+            self.break_length = step.length;
+        }
+        self.str_pomodoro = match step.kind {
+            StepKind::Focus => "Start a new Pomodoro",
+            StepKind::Break => "Start a new Break",
+            StepKind::LongBreak => "Start a new long Break",
+        }
+        .to_string();
+    }
+
+    // break after the last pomodoro, a part of its focus in flowtime mode
+    fn next_break(&self, focus: Duration) -> Duration {
+        let short = Duration::from_secs(self.break_duration * 60);
+        match self.timer.is_flowtime() {
+            true => self.timer.flowtime.break_after(focus),
+            false => self.timer.break_after(self.completed, short),
+        }
+    }
+
+    // the pomodoro is over, on time, at the end of its overtime or skipped
+    fn end_pomodoro(&mut self, outcome: Outcome) {
+        println!("end pomodoro");
+        let focus = self.elapsed_time.take();
+        self.finish_pomodoro(outcome);
+        self.paused_at = None;
+        if outcome == Outcome::Completed {
+            self.completed += 1;
+        }
+        if self.timer.schedule.is_some() {
+            self.next_step();
+            self.schedule_start();
+            return;
+        }
+        self.pomodoro_counter -= 1;
+        self.is_pomodoro = false;
+        self.break_length = self.next_break(focus);
+        let short = Duration::from_secs(self.break_duration * 60);
+        self.str_pomodoro = if self.timer.is_flowtime() || self.break_length == short {
+            "Start a new Break".to_string()
+        } else {
+            "Start a new long Break".to_string()
+        };
+        self.schedule_start();
+    }
+
+    fn end_break(&mut self) {
+        println!("end break");
+        self.overtime = None;
+        self.extended = Duration::default();
+        self.elapsed_time = Stopwatch::default();
+        if self.timer.schedule.is_some() {
+            self.next_step();
+            self.schedule_start();
+            return;
+        }
+        self.is_pomodoro = true;
+        self.str_pomodoro = "Start a new Pomodoro".to_string();
+        if self.pomodoro_counter > 0 {
+            self.schedule_start();
+        }
+    }
+
+    // planned length of the phase in progress
+    fn planned(&self) -> Duration {
+        let length = match (self.current_step(), self.is_pomodoro) {
+            (Some(step), _) => step.length,
+            (None, true) => Duration::from_secs(self.pomodoro_duration * 60),
+            (None, false) => self.break_length,
+        };
+        length + self.extended
+    }
+
+    // push back the end of the phase in progress, overtime included
+    fn extend(&mut self, minutes: u64) {
+        if self.is_pomodoro && self.pomodoro_start.is_none() {
+            self.str_pomodoro = "Start the Pomodoro to extend it".to_string();
+            return;
+        }
+        if self.is_pomodoro && self.timer.is_flowtime() {
+            self.str_pomodoro = "A flowtime focus has no end to extend".to_string();
+            return;
+        }
+        if self.overtime.take().is_some() {
+            self.extended += self.elapsed_time.elapsed().saturating_sub(self.planned());
+        }
+        self.extended += Duration::from_secs(minutes * 60);
+    }
+
+    // start the phase in progress over, the time spent so far isn't recorded
+    // start the phase over, the pomodoro so far is recorded as reset
+    fn reset(&mut self) {
+        // the pomodoro started over keeps its intention
+        let intention = self.intention.clone();
+        self.finish_pomodoro(Outcome::Reset);
+        self.intention = intention;
+        self.overtime = None;
+        self.extended = Duration::default();
+        self.elapsed_time = Stopwatch::default();
+        self.paused_at = None;
+        self.str_pomodoro = if self.is_pomodoro { "Start a new Pomodoro" } else { "Start a new Break" }
+            .to_string();
+    }
+
+    // count down to the next phase if it starts by itself
+    fn schedule_start(&mut self) {
+        if self.schedule_done() {
+            return;
+        }
+        let kind = if self.is_pomodoro { Phase::Pomodoro } else { Phase::Break };
+        if self.timer.auto_start(kind) {
+            let delay = self.timer.auto_start_delay.unwrap_or_default();
+            self.auto_start = Some(Instant::now() + delay);
+        }
+    }
+
+    // take the settings of a profile of the config file
+    fn use_profile(&mut self, name: String) {
+        let Some(timer) = self.config.profile(&name) else {
+            return;
+        };
+        let minutes = |dur: Duration| dur.as_secs() / 60;
+        if let Some(n) = timer.pomodoros {
+            self.pomodoro_counter = n;
+        }
+        if let Some(dur) = timer.pomodoro_duration {
+            self.pomodoro_duration = minutes(dur);
+        }
+        if let Some(dur) = timer.break_duration {
+            self.break_duration = minutes(dur);
+        }
+        self.void_after = timer.void_after;
+        if self.timer.schedule.is_some() {
+            // back to the alternation, unless the profile has a schedule too
+            self.is_pomodoro = true;
+            self.str_pomodoro = "Start a new Pomodoro".to_string();
+        }
+        self.timer = timer;
+        self.profile = Some(name);
+        self.step = 0;
+        self.load_step();
+    }
+
+    // save the pending pomodoro with its reflection
+    fn rate(&mut self, rating: Option<u8>) {
+        let Some(mut record) = self.pending.take() else {
+            return;
+        };
+        let reflection = std::mem::take(&mut self.reflection);
+        record.reflection = Some(reflection.trim().to_string()).filter(|t| !t.is_empty());
+        record.focus_rating = rating;
+        self.save_record(&record);
+    }
+
+    fn save_record(&mut self, record: &Record) {
+        if let Some(history) = &self.history {
+            self.save_error = history.append(record).err().map(|e| e.to_string());
+        }
+    }
 }
 
 impl Application for Pomodoro {
@@ -40,20 +358,38 @@ impl Application for Pomodoro {
     type Flags = ();
 
     fn new(_flags: ()) -> (Self, Command<Message>) {
-        (
-            Self {
-                pomodoro_duration: 0,
-                pomodoro_counter: 0,
-                break_duration: 0,
-                elapsed_time: Stopwatch {
-                    duration: Duration::default(),
-                    state: State::Idle,
-                },
-                is_pomodoro: true,
-                str_pomodoro: "Start a new Pomodoro".to_string(),
-            },
-            Command::none(),
-        )
+        let config = Config::load().unwrap_or_default();
+        let mut pomodoro = Self {
+            pomodoro_duration: 0,
+            pomodoro_counter: 0,
+            break_duration: 0,
+            break_length: Duration::default(),
+            completed: 0,
+            elapsed_time: Stopwatch::default(),
+            is_pomodoro: true,
+            str_pomodoro: "Start a new Pomodoro".to_string(),
+            history: History::open().ok(),
+            pomodoro_start: None,
+            marks: Vec::new(),
+            marks_checked: None,
+            note: String::new(),
+            paused_at: None,
+            void_after: config.timer.void_after,
+            auto_start: None,
+            overtime: None,
+            extended: Duration::default(),
+            prompts: config.prompts,
+            intention: String::new(),
+            reflection: String::new(),
+            pending: None,
+            save_error: None,
+            profile: None,
+            timer: config.timer.clone(),
+            step: 0,
+            config,
+        };
+        pomodoro.load_step();
+        (pomodoro, Command::none())
     }
 
     fn title(&self) -> String {
@@ -67,8 +403,14 @@ impl Application for Pomodoro {
         let font_size = 20;
         let pomodoro_duration_text = format!("{} minutes", self.pomodoro_duration);
         let break_duration_text = format!("{} minutes", self.break_duration);
-        column![
-            row![text("Pomodoro: "),]
+        let profiles: Vec<String> = self.config.profiles.keys().cloned().collect();
+        let mut content = column![
+            row![
+                text("Pomodoro: "),
+                pick_list(profiles, self.profile.clone(), Message::ProfileSelected)
+                    .placeholder("Profile"),
+            ]
+                .spacing(10)
                 .padding(10)
                 .align_items(iced::Alignment::Start),
             row![
@@ -109,18 +451,121 @@ impl Application for Pomodoro {
                 column![button("Stop").on_press(Message::StopPressed)]
                 .padding(10)
                 .align_items(iced::Alignment::Start),
+                column![button("Void").on_press(Message::VoidPressed)]
+                .padding(10)
+                .align_items(iced::Alignment::Start),
+                column![button("Skip").on_press(Message::SkipPressed)]
+                .padding(10)
+                .align_items(iced::Alignment::Start),
+                column![button("Reset").on_press(Message::ResetPressed)]
+                .padding(10)
+                .align_items(iced::Alignment::Start),
+                column![button("+1 min").on_press(Message::Extend(1))]
+                .padding(10)
+                .align_items(iced::Alignment::Start),
+                column![button("+5 min").on_press(Message::Extend(5))]
+                .padding(10)
+                .align_items(iced::Alignment::Start),
             ],
             row![text(self.str_pomodoro.clone()).size(font_size)]
                 .padding(10)
                 .align_items(iced::Alignment::Center),
-            row![text(format!("{} min {} sec",
-                    self.elapsed_time.duration.as_secs() / MINUTE,
-                    self.elapsed_time.duration.as_secs() % MINUTE
+            row![text(format!("{} min {} sec   ' {}  - {}",
+                    self.elapsed_time.elapsed().as_secs() / MINUTE,
+                    self.elapsed_time.elapsed().as_secs() % MINUTE,
+                    self.count_marks(InterruptionKind::Internal),
+                    self.count_marks(InterruptionKind::External)
                 )).size(font_size),]
                 .padding(10)
                 .align_items(iced::Alignment::Center),
-        ]
-        .into()
+            row![
+                text_input("Interruption note", &self.note)
+                    .on_input(Message::NoteChanged)
+                    .width(200),
+                button("' Internal").on_press(Message::Mark(InterruptionKind::Internal)),
+                button("- External").on_press(Message::Mark(InterruptionKind::External)),
+            ]
+            .spacing(10)
+            .padding(10)
+            .align_items(iced::Alignment::Center),
+        ];
+
+        if let (Some(step), Some(schedule)) = (self.current_step(), &self.timer.schedule) {
+            content = content.push(
+                row![text(format!("Step {}/{}: {} {}",
+                    self.step + 1,
+                    schedule.steps().len(),
+                    format_duration(step.length),
+                    step.kind.name()
+                ))]
+                .padding(10)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+        if self.timer.is_flowtime() && self.pomodoro_start.is_some() {
+            content = content.push(
+                row![button("Take a break").on_press(Message::MoveOn)]
+                    .padding(10)
+                    .align_items(iced::Alignment::Center),
+            );
+        }
+        if let Some(at) = self.overtime {
+            let secs = at.elapsed().as_secs();
+            content = content.push(
+                row![
+                    text(format!("+{} min {} sec", secs / MINUTE, secs % MINUTE))
+                        .size(font_size)
+                        .style(Color::from_rgb(0.8, 0.2, 0.6)),
+                    button("Move on").on_press(Message::MoveOn),
+                ]
+                .spacing(10)
+                .padding(10)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+        if let Some(at) = self.auto_start {
+            let left = at.saturating_duration_since(Instant::now());
+            let next = if self.is_pomodoro { "Pomodoro" } else { "Break" };
+            content = content.push(
+                row![
+                    text(format!("{} starts in {}s", next, left.as_millis().div_ceil(1000))),
+                    button("Wait").on_press(Message::CancelAutoStart),
+                ]
+                .spacing(10)
+                .padding(10)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+        if self.prompts.intention {
+            content = content.push(
+                row![text_input("What will you do?", &self.intention)
+                    .on_input(Message::IntentionChanged)
+                    .width(300)]
+                .padding(10),
+            );
+        }
+        if let Some(error) = &self.save_error {
+            content = content.push(
+                row![text(format!("Not saved: {}", error)).style(Color::from_rgb(0.8, 0.1, 0.1))]
+                    .padding(10),
+            );
+        }
+        if self.pending.is_some() {
+            let mut rating = row![
+                text_input("What did you get done?", &self.reflection)
+                    .on_input(Message::ReflectionChanged)
+                    .width(300),
+                text("Focus:"),
+            ]
+            .spacing(10)
+            .padding(10)
+            .align_items(iced::Alignment::Center);
+            for n in 1..=5 {
+                rating = rating.push(button(text(n)).on_press(Message::Rate(Some(n))));
+            }
+            content = content.push(rating.push(button("Skip").on_press(Message::Rate(None))));
+        }
+        content.into()
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
@@ -150,54 +595,138 @@ impl Application for Pomodoro {
                     self.break_duration -= 1;
                 }
             }
+            // run the schedule again
+            Message::StartPressed if self.schedule_done() => {
+                self.step = 0;
+                self.load_step();
+            }
             Message::StartPressed => {
-                if self.is_pomodoro && (self.pomodoro_counter == 0 || self.pomodoro_duration == 0) {
+                self.auto_start = None;
+                let flowtime = self.timer.is_flowtime();
+                // the steps of a schedule have their own lengths
+                let scheduled = self.current_step().is_some();
+                if !scheduled
+                    && self.is_pomodoro
+                    && (self.pomodoro_counter == 0 || (self.pomodoro_duration == 0 && !flowtime)) {
                     // no pomodoro number set
                     self.str_pomodoro = "Please set a valid Pomodoro number".to_string();
                     return Command::none();
-                } else if !self.is_pomodoro && self.break_duration == 0 {
+                } else if !scheduled && !self.is_pomodoro && self.break_duration == 0 {
                     self.str_pomodoro = "Please set a valid Break duration".to_string();
                     return Command::none();
                 }
-                self.elapsed_time.state = State::Ticking {
-                    last_tick: Instant::now(),
-                };
+                if !self.is_pomodoro && !scheduled {
+                    if !self.timer.is_flowtime() {
+                        self.break_length = self.next_break(Duration::default());
+                    }
+                }
+                self.elapsed_time.start();
+                self.paused_at = None;
+                if self.is_pomodoro {
+                    self.start_pomodoro();
+                }
             }
 
             Message::Tick(now) => {
-                if let State::Ticking { last_tick } = &mut self.elapsed_time.state {
-                    self.elapsed_time.duration += now - *last_tick;
-                    *last_tick = now;
+                if self.auto_start.map_or(false, |at| now >= at) {
+                    return self.update(Message::StartPressed);
+                }
+                if let (Some(paused_at), Some(limit)) = (self.paused_at, self.void_after) {
+                    if now - paused_at >= limit {
+                        self.void_pomodoro();
+                    }
+                }
+                if self.elapsed_time.is_running() {
                     if self.is_pomodoro {
+                        self.refresh_marks();
                         self.str_pomodoro = "Stay focused ^-^".to_string();
                     } else {
                         self.str_pomodoro = "Chill Bro :)".to_string();
                     }
                 }
 
-                if self.is_pomodoro &&
-                    self.elapsed_time.duration.as_secs() >= self.pomodoro_duration * 60 {
-                    // end of a pomodoro
-                    println!("end pomodoro");
-                    self.elapsed_time.state = State::Idle;
-                    self.elapsed_time.duration = Duration::default();
-                    self.pomodoro_counter -= 1;
-                    self.is_pomodoro = false;
-                    self.str_pomodoro = "Start a new Break".to_string();
-                } else if !self.is_pomodoro &&
-                   self.elapsed_time.duration.as_secs() >= self.break_duration * 60 {
-                    println!("end break");
-                    // end of break
-                    self.elapsed_time.state = State::Idle;
-                    self.elapsed_time.duration = Duration::default();
-                    self.is_pomodoro = true;
-                    self.str_pomodoro = "Start a new Pomodoro".to_string();
+                // the idle ticks only wait for a countdown or a void
+                let ticking = self.elapsed_time.is_running();
+                // a flowtime focus lasts until the user stops
+                let counts_up = self.is_pomodoro && self.timer.is_flowtime();
+                if ticking && !counts_up && self.elapsed_time.elapsed() >= self.planned() {
+                    if self.timer.overtime.unwrap_or(false) {
+                        // keep counting until the user moves on
+                        self.overtime.get_or_insert(now);
+                        self.str_pomodoro = "Time is up, move on when ready".to_string();
+                    } else if self.is_pomodoro {
+                        self.end_pomodoro(Outcome::Completed);
+                    } else {
+                        self.end_break();
+                    }
                 }
             }
 
             // stop timer
+            Message::StopPressed if self.overtime.is_some() => {
+                return self.update(Message::MoveOn);
+            }
             Message::StopPressed => {
-                self.elapsed_time.state = State::Idle;
+                if self.pomodoro_start.is_some() {
+                    self.paused_at = Some(Instant::now());
+                }
+                self.elapsed_time.pause();
+            }
+            Message::VoidPressed => {
+                self.void_pomodoro();
+            }
+            Message::SkipPressed if self.overtime.is_some() => {
+                return self.update(Message::MoveOn);
+            }
+            Message::SkipPressed => {
+                if !self.is_pomodoro {
+                    self.end_break();
+                } else if self.pomodoro_start.is_some() {
+                    self.end_pomodoro(Outcome::Skipped);
+                }
+            }
+            Message::ResetPressed => {
+                self.reset();
+            }
+            Message::Extend(minutes) => {
+                self.extend(minutes);
+            }
+
+            Message::NoteChanged(note) => {
+                self.note = note;
+            }
+            Message::Mark(kind) => {
+                self.mark(kind);
+            }
+            Message::IntentionChanged(intention) => {
+                self.intention = intention;
+            }
+            Message::ReflectionChanged(reflection) => {
+                self.reflection = reflection;
+            }
+            Message::Rate(rating) => {
+                self.rate(rating);
+            }
+            Message::MoveOn => {
+                let focusing = self.timer.is_flowtime() && self.pomodoro_start.is_some();
+                if self.is_pomodoro && (self.overtime.is_some() || focusing) {
+                    self.end_pomodoro(Outcome::Completed);
+                } else if self.overtime.is_some() {
+                    self.end_break();
+                }
+            }
+            Message::CancelAutoStart => {
+                self.auto_start = None;
+            }
+            Message::ProfileSelected(name) => {
+                if self.pomodoro_start.is_none() {
+                    self.use_profile(name);
+                }
+            }
+            Message::CloseRequested => {
+                // the pomodoro is saved even without reflection
+                self.rate(None);
+                return window::close();
             }
         }
 
@@ -205,11 +734,48 @@ impl Application for Pomodoro {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        match self.elapsed_time.state {
-            State::Idle => Subscription::none(),
-            State::Ticking { .. } => {
-                iced::time::every(Duration::from_millis(10)).map(Message::Tick)
+        let tick = match self.elapsed_time.is_running() {
+            true => iced::time::every(Duration::from_millis(10)).map(Message::Tick),
+            // a paused pomodoro is checked until it's voided
+            false if self.paused_at.is_some() && self.void_after.is_some() => {
+                iced::time::every(Duration::from_secs(1)).map(Message::Tick)
             }
-        }
+            false if self.auto_start.is_some() => {
+                iced::time::every(Duration::from_millis(100)).map(Message::Tick)
+            }
+            false => Subscription::none(),
+        };
+        // ' and - mark interruptions, like on the paper sheet
+        let keys = subscription::events_with(|event, status| match (event, status) {
+            (Event::Keyboard(keyboard::Event::CharacterReceived('\'')), iced::event::Status::Ignored) => {
+                Some(Message::Mark(InterruptionKind::Internal))
+            }
+            (Event::Keyboard(keyboard::Event::CharacterReceived('-')), iced::event::Status::Ignored) => {
+                Some(Message::Mark(InterruptionKind::External))
+            }
+            (Event::Window(window::Event::CloseRequested), _) => Some(Message::CloseRequested),
+            _ => None,
+        });
+        Subscription::batch(vec![tick, keys])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_records_the_pomodoro() {
+        let dir = std::env::temp_dir().join("pomodoro-gui-reset-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let (mut pomodoro, _) = Pomodoro::new(());
+        pomodoro.history = Some(History::new(dir.join("history.jsonl")));
+        pomodoro.start_pomodoro();
+        let _ = pomodoro.update(Message::ResetPressed);
+        let records = pomodoro.history.as_ref().unwrap().load().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].outcome, Outcome::Reset);
+        assert!(pomodoro.pomodoro_start.is_none());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
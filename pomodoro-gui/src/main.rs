@@ -3,6 +3,10 @@ use iced::{Application, Settings};
 use gui::Pomodoro;
 
 fn main() {
-    let _ = Pomodoro::run(Settings::default());
+    // the pending pomodoro is saved before the window closes
+    let _ = Pomodoro::run(Settings {
+        exit_on_close_request: false,
+        ..Settings::default()
+    });
     println!("Hello, world!");
 }
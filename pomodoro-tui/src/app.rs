@@ -1,5 +1,22 @@
 use std::time::{Duration, Instant};
 
+use crate::settings::Setting;
+use crate::theme::Theme;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use notify_rust::Notification;
+use pomodoro_core::calendar::Calendar;
+use pomodoro_core::config::{Config, TimerConfig};
+use pomodoro_core::duration::{format_duration, parse_duration};
+use pomodoro_core::flowtime::Stopwatch;
+use pomodoro_core::history::{
+    parse_focus_rating, History, Interruption, InterruptionKind, Outcome, Phase, Record, Running,
+};
+use pomodoro_core::inbox::{Capture, Inbox};
+use pomodoro_core::plan::{self, Block, SlotKind};
+use pomodoro_core::reminder::{Nag, Reminders};
+use pomodoro_core::schedule::{Step, StepKind};
+use pomodoro_core::tasks::{Task, TaskList};
+
 //
 //Constants
 //
@@ -7,17 +24,38 @@ use std::time::{Duration, Instant};
 const MIN_DURATION: Duration = Duration::from_secs(1 * 60);
 const DEFAULT_POMO_DUR: Duration = Duration::from_secs(50 * 60);
 const DEFAULT_BREAK_DUR: Duration = Duration::from_secs(15 * 60);
+// key presses closer than this are a held key
+const REPEAT_WINDOW: Duration = Duration::from_millis(200);
+// how often the interruptions marked from other shells are read
+const MARKS_REFRESH: Duration = Duration::from_secs(1);
+// used when the emoji is missing or the terminal can't draw it
+const POMO_ASCII: &str = "pomodoro";
+const TIMER_ASCII: &str = "break";
 
-#[derive(Debug, Default)]
+// the phase comes from the schedule, or alternates without one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppState {
-    #[default]
-    StopPomo,
-    RunPomo,
-    StopBreak,
-    RunBreak,
+    Stopped(Phase),
+    Running(Phase),
     NoMorePomo,
 }
 
+impl Default for AppState {
+    fn default() -> Self {
+        AppState::Stopped(Phase::Pomodoro)
+    }
+}
+
+impl AppState {
+    // `None` once there are no more phases
+    pub fn phase(self) -> Option<Phase> {
+        match self {
+            AppState::Stopped(phase) | AppState::Running(phase) => Some(phase),
+            AppState::NoMorePomo => None,
+        }
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Eq)]
 pub enum EditApp {
     #[default]
@@ -25,12 +63,90 @@ pub enum EditApp {
     PomoNum = 1,
     PomoDur = 2,
     BreakDur = 3,
+    Task = 4,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    #[default]
+    Timer,
+    Settings,
+    History,
+    Stats,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [Tab::Timer, Tab::Settings, Tab::History, Tab::Stats];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Timer => "Timer",
+            Tab::Settings => "Settings",
+            Tab::History => "History",
+            Tab::Stats => "Stats",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Tab::ALL
+            .iter()
+            .position(|&tab| tab == self)
+            .unwrap_or_default()
+    }
+}
+
+// text typed in the selected top bar field or setting
+#[derive(Debug, Default)]
+pub struct Input {
+    pub text: String,
+    // why the last submitted text was refused
+    pub error: Option<String>,
+    pub target: InputTarget,
+}
+
+// what the typed text is for
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputTarget {
+    // the selected top bar field, task or setting
+    #[default]
+    Field,
+    // note of an interruption of the running pomodoro
+    Interruption(InterruptionKind),
+    // idea for the inbox, typed in a popup over the clock
+    Capture,
+    // what the next pomodoro is for, asked before starting it
+    Intention,
+    // what got done, asked at the end of the pomodoro
+    Reflection,
+    // how focused the pomodoro was, asked after the reflection
+    FocusRating,
+    // time to add to the phase in progress
+    Extend,
+}
+
+// desktop notification of a reminder, sent aside so a slow notification
+// server never holds up the timer
+fn notify(nag: Nag) {
+    let body = format!(
+        "Idle for {}, time to start a pomodoro",
+        format_duration(nag.idle)
+    );
+    let mut notification = Notification::new();
+    notification.summary("Pomodoro").body(&body);
+    #[cfg(not(target_os = "macos"))]
+    if nag.urgent {
+        notification.urgency(notify_rust::Urgency::Critical);
+    }
+    std::thread::spawn(move || notification.show());
 }
 
 // App state
 #[derive(Debug)]
 pub struct App {
+    pub tab: Tab,
     pub edit_app: EditApp,
+    // `Some` while typing a value in the selected field
+    pub input: Option<Input>,
     pub pomo_num: u64,
     pub pomo_dur: Duration,
     // backup of `pomo_dur` so we can set this value at the end of the timer
@@ -42,6 +158,79 @@ pub struct App {
     pub should_quit: bool,
     pub pomo_emoji: String,
     pub timer_emoji: String,
+    pub theme: Theme,
+    // draw only ascii characters, no emoji or box drawing
+    pub ascii: bool,
+    // content of the config file, edited in the settings tab
+    pub config: Config,
+    // timer settings of the config or of the picked profile
+    timer: TimerConfig,
+    // name of the picked profile
+    pub profile: Option<String>,
+    // `Some` with the selected line while the profile picker is open
+    pub profile_picker: Option<usize>,
+    // pomodoros completed since the start, for the long breaks
+    completed: u32,
+    // day boundary and timezone of the stats
+    pub calendar: Calendar,
+    // index in `Setting::ALL` of the selected setting
+    pub setting: usize,
+    // where finished phases are saved, `None` to not save them
+    history: Option<History>,
+    // recorded phases, oldest first
+    pub records: Vec<Record>,
+    // first line of the history tab
+    pub history_scroll: usize,
+    // where the tasks are saved, `None` without tasks
+    task_list: Option<TaskList>,
+    // id of the task the pomodoros are for
+    pub task: Option<u32>,
+    // notes captured during the pomodoros, `None` without inbox
+    inbox: Option<Inbox>,
+    // start of the phase in progress
+    phase_start: Option<(Phase, DateTime<Local>)>,
+    // answer to the intention prompt, asked once per pomodoro
+    intention: Option<String>,
+    // finished pomodoro waiting for its reflection to be saved
+    pending: Option<Record>,
+    // interruptions of the pomodoro in progress
+    pub marks: Vec<Interruption>,
+    marks_checked: Option<Instant>,
+    // when the pomodoro in progress was paused
+    paused_at: Option<Instant>,
+    // pause after which the pomodoro is voided, never if `None`
+    void_after: Option<Duration>,
+    // time added to the phase in progress
+    extended: Duration,
+    // full length of the break in progress, long and flowtime ones included
+    break_length: Duration,
+    // focus counted up in flowtime mode
+    focus: Stopwatch,
+    // when the phase in progress reached its planned end, counting overtime
+    overtime: Option<Instant>,
+    // phase starting by itself and when, until cancelled
+    auto_start: Option<(Phase, Instant)>,
+    // index of the current step when the timer has a schedule
+    step: usize,
+    // planned starts of the pomodoros left today, see `[plan]`
+    planned: Vec<NaiveTime>,
+    // day, timer and lengths of the pomodoro and of the break the plan was made with
+    planned_for: Option<(NaiveDate, TimerConfig, Duration, Duration)>,
+    // today's events of the calendar of `[plan]`
+    meetings: Vec<Block>,
+    // meeting the running pomodoro runs into, `m` ends the pomodoro before it
+    meeting: Option<Block>,
+    // nags to start a pomodoro while the timer sits idle, see `[reminder]`
+    reminders: Reminders,
+    // last reminder sent since the timer went idle
+    pub nag: Option<Nag>,
+    // ring the terminal bell at the next draw, for an urgent reminder
+    bell: bool,
+    // result of the last save, shown to the user
+    pub message: Option<String>,
+    // last +/- step and how many times in a row it was repeated
+    last_step: Option<Instant>,
+    step_repeat: u32,
     start_time: Instant,
     pub state: AppState,
 }
@@ -56,15 +245,55 @@ impl App {
     // constructor
     pub fn new() -> Self {
         App {
+            tab: Tab::Timer,
             edit_app: EditApp::Nothing,
-            pomo_emoji: emojis::get_by_shortcode("tomato").unwrap().to_string(),
-            timer_emoji: emojis::get_by_shortcode("timer_clock").unwrap().to_string(),
+            input: None,
+            pomo_emoji: emoji("tomato", POMO_ASCII),
+            timer_emoji: emoji("timer_clock", TIMER_ASCII),
+            theme: Theme::default(),
+            ascii: false,
+            config: Config::default(),
+            timer: TimerConfig::default(),
+            profile: None,
+            profile_picker: None,
+            completed: 0,
+            calendar: Calendar::default(),
+            setting: 0,
+            history: None,
+            records: Vec::new(),
+            history_scroll: 0,
+            task_list: None,
+            task: None,
+            inbox: None,
+            phase_start: None,
+            intention: None,
+            pending: None,
+            marks: Vec::new(),
+            marks_checked: None,
+            paused_at: None,
+            void_after: None,
+            extended: Duration::ZERO,
+            break_length: DEFAULT_BREAK_DUR,
+            focus: Stopwatch::default(),
+            overtime: None,
+            auto_start: None,
+            step: 0,
+            planned: Vec::new(),
+            planned_for: None,
+            meetings: Vec::new(),
+            meeting: None,
+            reminders: Reminders::default(),
+            nag: None,
+            bell: false,
+            message: None,
+            last_step: None,
+            step_repeat: 0,
             pomo_num: 1,
             pomo_dur: DEFAULT_POMO_DUR,
             pomo_dur_bk: DEFAULT_POMO_DUR,
             break_dur: DEFAULT_BREAK_DUR,
             break_dur_bk: DEFAULT_BREAK_DUR,
-            state: AppState::StopPomo,
+            state: AppState::Stopped(Phase::Pomodoro),
             start_time: Instant::now(),
             counter: 0,
             should_quit: false,
@@ -79,15 +308,268 @@ impl App {
 
     // set running to false to quit the app
     pub fn quit(&mut self) {
+        if let Some((kind, _)) = self.phase_start {
+            // a phase in overtime already reached its end
+            let outcome = match self.overtime {
+                Some(_) => Outcome::Completed,
+                None => Outcome::Interrupted,
+            };
+            self.finish_phase(kind, outcome);
+        }
+        if let Some(record) = self.pending.take() {
+            self.save_record(record);
+        }
         self.should_quit = true;
     }
 
+    // use the values of the config file
+    pub fn set_config(&mut self, config: Config) {
+        match Calendar::from_config(&config.stats) {
+            Ok(calendar) => self.calendar = calendar,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+        self.timer = config.timer.clone();
+        // a snooze lasts until the reminders change
+        let reminder = config.reminder.or_plan(&config.plan);
+        if *self.reminders.config() != reminder {
+            self.reminders = Reminders::new(reminder);
+        }
+        self.config = config;
+        for setting in Setting::ALL {
+            self.use_setting(setting);
+        }
+        self.plan_day();
+    }
+
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.config.profiles.keys().map(String::as_str).collect()
+    }
+
+    pub fn open_profiles(&mut self) {
+        let names = self.profile_names();
+        if names.is_empty() {
+            self.message = Some(String::from("no [profiles] in the config file"));
+            return;
+        }
+        let current = self.profile.as_deref();
+        let selected = names.iter().position(|&name| Some(name) == current);
+        self.profile_picker = Some(selected.unwrap_or(0));
+    }
+
+    pub fn close_profiles(&mut self) {
+        self.profile_picker = None;
+    }
+
+    pub fn next_profile(&mut self, down: bool) {
+        let count = self.config.profiles.len();
+        if let Some(selected) = self.profile_picker.as_mut().filter(|_| count > 0) {
+            *selected = match down {
+                true => (*selected + 1) % count,
+                false => (*selected + count - 1) % count,
+            };
+        }
+    }
+
+    // use the profile selected in the picker
+    pub fn pick_profile(&mut self) {
+        let Some(selected) = self.profile_picker.take() else {
+            return;
+        };
+        if let Some(name) = self
+            .profile_names()
+            .get(selected)
+            .map(|name| name.to_string())
+        {
+            self.use_profile(&name);
+        }
+    }
+
+    pub fn use_profile(&mut self, name: &str) {
+        if self.phase_start.is_some() {
+            self.message = Some(String::from("finish the phase to change profile"));
+            return;
+        }
+        let Some(timer) = self.config.profile(name) else {
+            self.message = Some(format!("no profile '{}'", name));
+            return;
+        };
+        if let Some(n) = timer.pomodoros {
+            self.pomo_num = n.into();
+        }
+        if let Some(dur) = timer.pomodoro_duration {
+            self.pomo_dur = dur;
+            self.pomo_dur_bk = dur;
+        }
+        if let Some(dur) = timer.break_duration {
+            self.break_dur = dur;
+            self.break_dur_bk = dur;
+        }
+        self.void_after = timer.void_after;
+        let had_schedule = self.timer.schedule.is_some();
+        self.timer = timer;
+        self.profile = Some(name.to_string());
+        if had_schedule || self.timer.schedule.is_some() {
+            self.start_schedule();
+        }
+    }
+
+    // go back to the first step of the schedule, or to a pomodoro without one
+    pub fn start_schedule(&mut self) {
+        self.step = 0;
+        self.auto_start = None;
+        self.start_time = Instant::now();
+        self.state = AppState::default();
+        self.load_step();
+    }
+
+    pub fn has_schedule(&self) -> bool {
+        self.timer.schedule.is_some()
+    }
+
+    // current step and the number of steps, `None` without a schedule
+    pub fn schedule_step(&self) -> Option<(Step, usize, usize)> {
+        let steps = self.timer.schedule.as_ref()?.steps();
+        steps
+            .get(self.step)
+            .map(|step| (*step, self.step, steps.len()))
+    }
+
+    // save finished phases in `history` and show the old ones
+    pub fn set_history(&mut self, history: History) {
+        match history.load() {
+            Ok(records) => self.records = records,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+        self.history = Some(history);
+    }
+
+    pub fn set_tasks(&mut self, tasks: TaskList) {
+        self.task = tasks.pending().next().map(|task| task.id);
+        self.task_list = Some(tasks);
+    }
+
+    pub fn set_inbox(&mut self, inbox: Inbox) {
+        self.inbox = Some(inbox);
+    }
+
+    pub fn captures(&self) -> &[Capture] {
+        self.inbox.as_ref().map_or(&[], |inbox| inbox.captures())
+    }
+
+    // the inbox is looked at during the breaks
+    pub fn showing_inbox(&self) -> bool {
+        self.state.phase() == Some(Phase::Break) && !self.captures().is_empty()
+    }
+
+    // turn the oldest capture into a task
+    pub fn capture_to_task(&mut self) {
+        let (Some(inbox), Some(tasks)) = (self.inbox.as_mut(), self.task_list.as_mut()) else {
+            return;
+        };
+        match inbox.into_task(0, tasks, None) {
+            Ok(id) => self.task = self.task.or(Some(id)),
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    pub fn discard_capture(&mut self) {
+        if let Some(Err(e)) = self.inbox.as_mut().map(|inbox| inbox.discard(0)) {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    pub fn start_capture(&mut self) {
+        self.ask(InputTarget::Capture);
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        self.task_list.as_ref().map_or(&[], |tasks| tasks.tasks())
+    }
+
+    // tasks not done yet, oldest first
+    pub fn pending_tasks(&self) -> Vec<&Task> {
+        self.task_list
+            .iter()
+            .flat_map(|tasks| tasks.pending())
+            .collect()
+    }
+
+    // name of the selected task
+    pub fn task_name(&self) -> Option<&str> {
+        let id = self.task?;
+        let task = self.task_list.as_ref()?.get(id)?;
+        Some(&task.name)
+    }
+
+    pub fn next_task(&mut self, down: bool) {
+        let ids: Vec<u32> = self.pending_tasks().iter().map(|task| task.id).collect();
+        if ids.is_empty() {
+            self.task = None;
+            return;
+        }
+        let current = self.task.and_then(|id| ids.iter().position(|&i| i == id));
+        let next = match (current, down) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1) % ids.len(),
+            (Some(i), false) => (i + ids.len() - 1) % ids.len(),
+        };
+        self.task = Some(ids[next]);
+    }
+
+    // mark the selected task as done and select the next one
+    pub fn finish_task(&mut self) {
+        let (Some(id), Some(tasks)) = (self.task, self.task_list.as_mut()) else {
+            return;
+        };
+        if let Err(e) = tasks.mark_done(id) {
+            self.message = Some(e.to_string());
+        }
+        self.task = None;
+        self.next_task(true);
+    }
+
+    pub fn select_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+    }
+
+    pub fn next_tab(&mut self) {
+        self.tab = Tab::ALL[(self.tab.index() + 1) % Tab::ALL.len()];
+    }
+
+    pub fn prev_tab(&mut self) {
+        self.tab = Tab::ALL[(self.tab.index() + Tab::ALL.len() - 1) % Tab::ALL.len()];
+    }
+
+    pub fn next_setting(&mut self) {
+        self.setting = (self.setting + 1) % Setting::ALL.len();
+    }
+
+    pub fn prev_setting(&mut self) {
+        self.setting = (self.setting + Setting::ALL.len() - 1) % Setting::ALL.len();
+    }
+
+    pub fn scroll_history(&mut self, down: bool) {
+        self.history_scroll = if down {
+            (self.history_scroll + 1).min(self.records.len().saturating_sub(1))
+        } else {
+            self.history_scroll.saturating_sub(1)
+        };
+    }
+
+    // replace emoji and borders with plain ascii
+    pub fn use_ascii(&mut self) {
+        self.ascii = true;
+        self.pomo_emoji = POMO_ASCII.to_string();
+        self.timer_emoji = TIMER_ASCII.to_string();
+    }
+
     pub fn next_edit(&mut self) {
         self.edit_app = match self.edit_app {
             EditApp::Nothing => EditApp::PomoNum,
             EditApp::PomoNum => EditApp::PomoDur,
             EditApp::PomoDur => EditApp::BreakDur,
-            EditApp::BreakDur => EditApp::Nothing,
+            EditApp::BreakDur => EditApp::Task,
+            EditApp::Task => EditApp::Nothing,
         }
     }
 
@@ -96,6 +578,7 @@ impl App {
             EditApp::PomoNum => self.increment_pomo_num(),
             EditApp::PomoDur => self.increment_pomo_dur(),
             EditApp::BreakDur => self.increment_break_dur(),
+            EditApp::Task => self.next_task(true),
             EditApp::Nothing => (),
         }
     }
@@ -105,86 +588,850 @@ impl App {
             EditApp::PomoNum => self.decrement_pomo_num(),
             EditApp::PomoDur => self.decrement_pomo_dur(),
             EditApp::BreakDur => self.decrement_break_dur(),
+            EditApp::Task => self.next_task(false),
             EditApp::Nothing => (),
         }
     }
 
+    // start typing a value in the selected field
+    pub fn start_input(&mut self, first: Option<char>) {
+        let text = match (self.tab, first) {
+            (Tab::Timer, _) if self.edit_app == EditApp::Nothing => return,
+            (_, Some(c)) => String::from(c),
+            // settings start from the current value
+            (Tab::Settings, None) => Setting::ALL[self.setting]
+                .value(&self.config)
+                .unwrap_or_default(),
+            (_, None) => String::new(),
+        };
+        self.input = Some(Input {
+            text,
+            ..Default::default()
+        });
+    }
+
+    // typed text of the selected field, `None` while typing something else
+    pub fn field_input(&self) -> Option<&Input> {
+        self.input
+            .as_ref()
+            .filter(|input| input.target == InputTarget::Field)
+    }
+
+    pub fn in_pomodoro(&self) -> bool {
+        matches!(self.phase_start, Some((Phase::Pomodoro, _)))
+    }
+
+    // ask the note of an interruption, only during a pomodoro
+    pub fn start_mark(&mut self, kind: InterruptionKind) {
+        if self.in_pomodoro() {
+            self.input = Some(Input {
+                target: InputTarget::Interruption(kind),
+                ..Default::default()
+            });
+        }
+    }
+
+    pub fn push_input(&mut self, c: char) {
+        if let Some(input) = self.input.as_mut() {
+            input.text.push(c);
+        }
+    }
+
+    pub fn pop_input(&mut self) {
+        if let Some(input) = self.input.as_mut() {
+            input.text.pop();
+        }
+    }
+
+    pub fn cancel_input(&mut self) {
+        // the pomodoro is saved even without reflection
+        if let Some(record) = self.pending.take() {
+            self.save_record(record);
+        }
+        self.input = None;
+    }
+
+    fn ask(&mut self, target: InputTarget) {
+        self.input = Some(Input {
+            target,
+            ..Default::default()
+        });
+    }
+
+    // apply the typed value, on error keep the input open and show why
+    pub fn submit_input(&mut self) {
+        let Some((text, target)) = self
+            .input
+            .as_ref()
+            .map(|input| (input.text.clone(), input.target))
+        else {
+            return;
+        };
+        let result = match (target, self.tab) {
+            (InputTarget::Interruption(kind), _) => {
+                self.mark(kind, &text);
+                Ok(())
+            }
+            (InputTarget::Capture, _) => self.capture(&text),
+            (InputTarget::Intention, _) => {
+                self.intention = Some(text.trim().to_string());
+                self.toggle_start_stop();
+                Ok(())
+            }
+            (InputTarget::Reflection, _) => {
+                if let Some(record) = self.pending.as_mut() {
+                    record.reflection = Some(text.trim().to_string()).filter(|t| !t.is_empty());
+                }
+                self.ask(InputTarget::FocusRating);
+                Ok(())
+            }
+            (InputTarget::FocusRating, _) => self.rate_focus(&text),
+            (InputTarget::Extend, _) => self.extend(&text),
+            (InputTarget::Field, Tab::Settings) => self.apply_setting(&text),
+            (InputTarget::Field, _) => self.apply_input(&text),
+        };
+        match result {
+            // unless the answer led to another question
+            Ok(()) if self.input.as_ref().map(|input| input.target) == Some(target) => {
+                self.input = None
+            }
+            Ok(()) => {}
+            Err(e) => {
+                if let Some(input) = self.input.as_mut() {
+                    input.error = Some(e);
+                }
+            }
+        }
+    }
+
     pub fn toggle_start_stop(&mut self) {
+        self.auto_start = None;
         match self.state {
-            AppState::RunPomo => {
-                self.state = AppState::StopPomo;
+            // the schedule picks the next phase instead of the alternation
+            AppState::Stopped(Phase::Pomodoro)
+                if self.pomo_dur == Duration::ZERO && self.timer.schedule.is_some() =>
+            {
+                self.next_step()
+            }
+            AppState::Stopped(Phase::Break)
+                if self.break_dur == Duration::ZERO && self.timer.schedule.is_some() =>
+            {
+                self.next_step()
             }
-            AppState::StopPomo => {
+            AppState::Running(Phase::Pomodoro)
+                if self.overtime.is_some() || self.timer.is_flowtime() =>
+            {
+                self.end_pomodoro(Outcome::Completed)
+            }
+            AppState::Running(Phase::Pomodoro) => {
+                self.paused_at = Some(Instant::now());
+                self.state = AppState::Stopped(Phase::Pomodoro);
+            }
+            AppState::Stopped(Phase::Pomodoro) => {
+                self.paused_at = None;
+                if self.asks_intention() {
+                    self.ask(InputTarget::Intention);
+                    return;
+                }
                 if self.pomo_dur == Duration::ZERO {
                     // TODO: ring a bell
                     // decrease pomo_num
                     self.decrement_pomo_num();
                     self.pomo_dur = self.pomo_dur_bk;
-                    self.break_dur = self.break_dur_bk;
+                    self.break_length = match self.timer.is_flowtime() {
+                        true => self.timer.flowtime.break_after(self.focus.take()),
+                        false => self.timer.break_after(self.completed, self.break_dur_bk),
+                    };
+                    self.break_dur = self.break_length;
                     self.start_time = Instant::now();
-                    self.state = AppState::StopBreak;
+                    self.state = AppState::Stopped(Phase::Break);
                 } else {
+                    if self.phase_start.is_none() {
+                        self.warn_meeting(Local::now().time());
+                    }
                     self.start_time = Instant::now();
-                    self.state = AppState::RunPomo;
+                    self.start_phase(Phase::Pomodoro);
+                    self.state = AppState::Running(Phase::Pomodoro);
                 }
                 if self.pomo_num == 0 {
                     self.state = AppState::NoMorePomo
                 }
             }
-            AppState::RunBreak => {
-                self.state = AppState::StopBreak;
+            AppState::Running(Phase::Break) if self.overtime.is_some() => {
+                self.end_break(Outcome::Completed)
+            }
+            AppState::Running(Phase::Break) => {
+                self.state = AppState::Stopped(Phase::Break);
             }
-            AppState::StopBreak => {
+            AppState::Stopped(Phase::Break) => {
                 if self.break_dur == Duration::ZERO {
                     self.break_dur = self.break_dur_bk;
                     self.pomo_dur = self.pomo_dur_bk;
                     self.start_time = Instant::now();
 
-                    self.state = AppState::StopPomo;
+                    self.state = AppState::Stopped(Phase::Pomodoro);
                 } else {
                     self.start_time = Instant::now();
-                    self.state = AppState::RunBreak;
+                    self.start_phase(Phase::Break);
+                    self.state = AppState::Running(Phase::Break);
                 }
             }
+            // run the schedule again
+            AppState::NoMorePomo if self.timer.schedule.is_some() => self.start_schedule(),
             AppState::NoMorePomo => {
-                if self.pomo_num > 0 {
+                if self.asks_intention() {
+                    self.ask(InputTarget::Intention);
+                } else if self.pomo_num > 0 {
                     self.start_time = Instant::now();
-                    self.state = AppState::RunPomo
+                    self.start_phase(Phase::Pomodoro);
+                    self.state = AppState::Running(Phase::Pomodoro)
                 }
             }
         }
     }
 
+    // abandon the pomodoro in progress, it doesn't count
+    pub fn void_pomodoro(&mut self) {
+        if !self.in_pomodoro() {
+            return;
+        }
+        self.finish_phase(Phase::Pomodoro, Outcome::Voided);
+        self.paused_at = None;
+        self.pomo_dur = self.pomo_dur_bk;
+        self.focus = Stopwatch::default();
+        self.state = AppState::Stopped(Phase::Pomodoro);
+        self.message = Some(String::from("pomodoro voided"));
+    }
+
+    // end the phase in progress early and go to the next one
+    pub fn skip_phase(&mut self) {
+        // a phase in overtime already reached its end
+        let outcome = match self.overtime {
+            Some(_) => Outcome::Completed,
+            None => Outcome::Skipped,
+        };
+        match self.state.phase() {
+            // a pomodoro never started isn't used up
+            Some(Phase::Pomodoro) if self.phase_start.is_none() => {
+                self.message = Some(String::from("start the pomodoro to skip it"));
+            }
+            Some(Phase::Pomodoro) => self.end_pomodoro(outcome),
+            Some(Phase::Break) => self.end_break(outcome),
+            None => {}
+        }
+    }
+
+    // start the phase in progress over, the time spent so far is recorded as
+    // reset and doesn't count
+    pub fn reset_phase(&mut self) {
+        self.paused_at = None;
+        match self.state.phase() {
+            Some(Phase::Pomodoro) => {
+                // the pomodoro started over keeps its intention
+                let intention = self.intention.clone();
+                self.finish_phase(Phase::Pomodoro, Outcome::Reset);
+                self.intention = intention;
+                self.pomo_dur = self.pomo_dur_bk;
+                self.focus = Stopwatch::default();
+                self.state = AppState::Stopped(Phase::Pomodoro);
+            }
+            Some(Phase::Break) => {
+                self.finish_phase(Phase::Break, Outcome::Reset);
+                self.break_dur = self.break_length;
+                self.state = AppState::Stopped(Phase::Break);
+            }
+            None => return,
+        }
+        self.message = Some(String::from("phase reset"));
+    }
+
+    pub fn start_extend(&mut self) {
+        if self.phase_start.is_none() {
+            self.message = Some(String::from("start the phase to extend it"));
+            return;
+        }
+        self.ask(InputTarget::Extend);
+    }
+
+    // void the pomodoro once paused for longer than the configured time
+    pub fn check_pause(&mut self) {
+        if let (Some(paused_at), Some(limit)) = (self.paused_at, self.void_after) {
+            if paused_at.elapsed() >= limit {
+                self.void_pomodoro();
+            }
+        }
+    }
+
+    // the phase starting by itself and the time left before it starts
+    pub fn auto_start_in(&self) -> Option<(Phase, Duration)> {
+        self.auto_start
+            .map(|(kind, at)| (kind, at.saturating_duration_since(Instant::now())))
+    }
+
+    // keep the next phase waiting for the user, false if it wasn't starting
+    pub fn cancel_auto_start(&mut self) -> bool {
+        self.auto_start.take().is_some()
+    }
+
+    // start the next phase once its countdown is over
+    pub fn check_auto_start(&mut self) {
+        if self.auto_start.is_some_and(|(_, at)| at <= Instant::now()) {
+            self.toggle_start_stop();
+        }
+    }
+
+    // next planned start of a pomodoro
+    pub fn next_planned(&self) -> Option<NaiveTime> {
+        self.planned.first().copied()
+    }
+
+    // start the pomodoro at its planned time if `[plan]` says so, the missed
+    // starts are dropped
+    pub fn check_plan(&mut self) {
+        // the lengths or the day changed since the plan was made
+        let today = Local::now().date_naive();
+        if !self
+            .planned_for
+            .as_ref()
+            .is_some_and(|(date, timer, focus, short)| {
+                *date == today
+                    && *timer == self.timer
+                    && *focus == self.pomo_dur_bk
+                    && *short == self.break_dur_bk
+            })
+        {
+            self.plan_day();
+        }
+        let now = Local::now().time();
+        let due = self.planned.iter().take_while(|at| **at <= now).count();
+        if due == 0 {
+            return;
+        }
+        self.planned.drain(..due);
+        if self.config.plan.auto_start == Some(true)
+            && self.state == AppState::Stopped(Phase::Pomodoro)
+            && self.phase_start.is_none()
+            && self.input.is_none()
+        {
+            self.toggle_start_stop();
+        }
+    }
+
+    // whether the terminal bell should ring, once per urgent reminder
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell)
+    }
+
+    // end the running pomodoro when the meeting it runs into starts
+    pub fn fit_meeting(&mut self) {
+        self.fit_meeting_at(Local::now().time());
+    }
+
+    fn fit_meeting_at(&mut self, now: NaiveTime) {
+        let Some(meeting) = self.meeting.take().filter(|_| self.in_pomodoro()) else {
+            return;
+        };
+        if let Ok(left) = (meeting.start - now).to_std() {
+            if left < self.pomo_dur {
+                self.pomo_dur = left;
+                self.start_time = Instant::now();
+            }
+            self.message = Some(format!(
+                "the pomodoro ends at {}",
+                plan::format_time(meeting.start)
+            ));
+        }
+    }
+
+    // remind to start a pomodoro while the timer sits idle in the work hours
+    pub fn check_reminder(&mut self) {
+        let idle = self.phase_start.is_none()
+            && self.auto_start.is_none()
+            && self.input.is_none()
+            && matches!(
+                self.state,
+                AppState::Stopped(Phase::Pomodoro) | AppState::NoMorePomo
+            );
+        if !idle {
+            self.nag = None;
+        }
+        if let Some(nag) = self.reminders.check(idle, Local::now()) {
+            self.nag = Some(nag);
+            self.bell = nag.urgent;
+            notify(nag);
+        }
+    }
+
+    pub fn snooze_reminders(&mut self) {
+        if self.reminders.config().every.is_none() {
+            self.message = Some(String::from("no reminders in the config file"));
+            return;
+        }
+        let until = self.reminders.snooze(Local::now());
+        self.nag = None;
+        self.message = Some(format!(
+            "reminders snoozed until {}",
+            plan::format_time(until.time())
+        ));
+    }
+
+    pub fn snooze_length(&self) -> Duration {
+        self.reminders.config().snooze()
+    }
+
+    // time past the planned end of the phase in progress
+    pub fn overtime(&self) -> Option<Duration> {
+        self.overtime.map(|at| at.elapsed())
+    }
+
+    // focus counted up so far, `None` unless in flowtime mode
+    pub fn focus_time(&self) -> Option<Duration> {
+        self.timer.is_flowtime().then(|| self.focus.elapsed())
+    }
+
     pub fn update_timer(&mut self) {
+        if self.overtime.is_some() || self.timer.is_flowtime() {
+            self.refresh_marks();
+            return;
+        }
         let now = Instant::now();
         let elapsed = now - self.start_time;
         if let Some(res) = self.pomo_dur.checked_sub(elapsed) {
             self.start_time = now;
             self.pomo_dur = res;
-        } else {
+            self.refresh_marks();
+        } else if self.timer.overtime.unwrap_or(false) {
+            self.overtime = Some(self.start_time + self.pomo_dur);
             self.pomo_dur = Duration::ZERO;
-            self.state = AppState::StopPomo;
-            self.toggle_start_stop();
+        } else {
+            self.end_pomodoro(Outcome::Completed);
         }
     }
 
     pub fn update_break_timer(&mut self) {
+        if self.overtime.is_some() {
+            return;
+        }
         let now = Instant::now();
         let elapsed = now - self.start_time;
         if let Some(res) = self.break_dur.checked_sub(elapsed) {
             self.start_time = now;
             self.break_dur = res;
-        } else {
+        } else if self.timer.overtime.unwrap_or(false) {
+            self.overtime = Some(self.start_time + self.break_dur);
             self.break_dur = Duration::ZERO;
-            self.state = AppState::StopBreak;
-            self.toggle_start_stop();
+        } else {
+            self.end_break(Outcome::Completed);
         }
     }
 
     ///
     /// Priv methods
+    // the pomodoro is over, on time, at the end of its overtime or skipped
+    fn end_pomodoro(&mut self, outcome: Outcome) {
+        self.finish_phase(Phase::Pomodoro, outcome);
+        if outcome == Outcome::Completed {
+            self.completed += 1;
+        }
+        self.paused_at = None;
+        self.pomo_dur = Duration::ZERO;
+        self.state = AppState::Stopped(Phase::Pomodoro);
+        self.toggle_start_stop();
+        if let AppState::Stopped(kind) = self.state {
+            self.schedule_start(kind);
+        }
+    }
+
+    fn end_break(&mut self, outcome: Outcome) {
+        self.finish_phase(Phase::Break, outcome);
+        self.break_dur = Duration::ZERO;
+        self.state = AppState::Stopped(Phase::Break);
+        self.toggle_start_stop();
+        if let AppState::Stopped(kind) = self.state {
+            self.schedule_start(kind);
+        }
+    }
+
+    // place the pomodoros of the day with the current lengths, like
+    // `pomodoro plan`, from now if `[plan]` has no start
+    fn plan_day(&mut self) {
+        let today = Local::now().date_naive();
+        self.planned_for = Some((
+            today,
+            self.timer.clone(),
+            self.pomo_dur_bk,
+            self.break_dur_bk,
+        ));
+        self.meetings = match self.config.plan.meetings(today) {
+            Ok(meetings) => meetings,
+            Err(e) => {
+                self.message = Some(format!("cannot read the calendar: {}", e));
+                Vec::new()
+            }
+        };
+        let Some(until) = self.config.plan.until else {
+            self.planned.clear();
+            return;
+        };
+        let now = Local::now().time();
+        let from = self.config.plan.from.unwrap_or(now);
+        let steps = plan::day_steps(&self.timer, self.pomo_dur_bk, self.break_dur_bk);
+        let mut busy = self.config.plan.busy();
+        busy.extend(self.meetings.iter().cloned());
+        self.planned = plan::plan(from, until, &steps, &busy)
+            .into_iter()
+            .filter(|slot| slot.kind == SlotKind::Step(StepKind::Focus) && slot.start >= now)
+            .map(|slot| slot.start)
+            .collect();
+    }
+
+    // warn when the pomodoro starting would run into a meeting
+    fn warn_meeting(&mut self, now: NaiveTime) {
+        self.meeting = match self.timer.is_flowtime() {
+            true => None,
+            false => plan::overlap(&self.meetings, now, self.pomo_dur).cloned(),
+        };
+        let Some(meeting) = &self.meeting else {
+            return;
+        };
+        let name = meeting.name.as_deref().unwrap_or("a meeting");
+        let start = plan::format_time(meeting.start);
+        if meeting.start - now < chrono::Duration::minutes(1) {
+            self.message = Some(format!(
+                "{} runs until {}",
+                name,
+                plan::format_time(meeting.end)
+            ));
+            self.meeting = None;
+        } else {
+            self.message = Some(format!(
+                "{} starts at {}, m ends the pomodoro then",
+                name, start
+            ));
+        }
+    }
+
+    fn next_step(&mut self) {
+        self.step += 1;
+        self.start_time = Instant::now();
+        self.load_step();
+    }
+
+    // take the state and length of the current step, the pomodoros left
+    // are the focus steps left
+    fn load_step(&mut self) {
+        let Some(schedule) = &self.timer.schedule else {
+            return;
+        };
+        let steps = schedule.steps().get(self.step..).unwrap_or_default();
+        self.pomo_num = steps
+            .iter()
+            .filter(|step| step.kind == StepKind::Focus)
+            .count() as u64;
+        let Some(step) = steps.first().copied() else {
+            self.state = AppState::NoMorePomo;
+            return;
+        };
+        match step.kind.phase() {
+            Phase::Pomodoro => {
+                self.pomo_dur = step.length;
+                self.pomo_dur_bk = step.length;
+            }
+            Phase::Break => {
+                self.break_dur = step.length;
+                self.break_length = step.length;
+            }
+        }
+        self.state = AppState::Stopped(step.kind.phase());
+    }
+
+    // count down to the next phase if it starts by itself, right away without delay
+    fn schedule_start(&mut self, kind: Phase) {
+        if !self.timer.auto_start(kind) {
+            return;
+        }
+        match self.timer.auto_start_delay.unwrap_or_default() {
+            Duration::ZERO => self.toggle_start_stop(),
+            delay => self.auto_start = Some((kind, Instant::now() + delay)),
+        }
+    }
+
+    // a paused phase keeps its original start
+    fn start_phase(&mut self, kind: Phase) {
+        if kind == Phase::Pomodoro && self.timer.is_flowtime() {
+            self.focus.start();
+        }
+        if self.phase_start.is_some() {
+            return;
+        }
+        let running = Running {
+            kind,
+            start: Local::now(),
+        };
+        if let Some(Err(e)) = self.history.as_ref().map(|h| h.set_running(Some(&running))) {
+            self.message = Some(e.to_string());
+        }
+        self.phase_start = Some((kind, running.start));
+        self.marks.clear();
+        self.marks_checked = None;
+    }
+
+    fn mark(&mut self, kind: InterruptionKind, note: &str) {
+        let note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+        let interruption = Interruption::now(kind, note);
+        match &self.history {
+            Some(history) => {
+                if let Err(e) = history.mark(&interruption) {
+                    self.message = Some(e.to_string());
+                }
+                self.marks_checked = None;
+                self.refresh_marks();
+            }
+            None => self.marks.push(interruption),
+        }
+    }
+
+    // the intention is asked before a new pomodoro, not when resuming one
+    fn asks_intention(&self) -> bool {
+        self.config.prompts.intention
+            && self.intention.is_none()
+            && self.phase_start.is_none()
+            && self.pomo_num > 0
+            && self.pomo_dur > Duration::ZERO
+    }
+
+    fn rate_focus(&mut self, text: &str) -> Result<(), String> {
+        let rating = parse_focus_rating(text)?;
+        if let Some(mut record) = self.pending.take() {
+            record.focus_rating = rating;
+            self.save_record(record);
+        }
+        Ok(())
+    }
+
+    // push back the end of the phase in progress, overtime included
+    fn extend(&mut self, text: &str) -> Result<(), String> {
+        if self.focus_time().is_some() && self.in_pomodoro() {
+            return Err(String::from("a flowtime focus has no end to extend"));
+        }
+        let extra = input_duration(text)?;
+        if extra == Duration::ZERO {
+            return Err(String::from("extend by more than 0"));
+        }
+        if let Some(at) = self.overtime.take() {
+            self.extended += at.elapsed();
+            self.start_time = Instant::now();
+        }
+        self.extended += extra;
+        match self.state.phase() {
+            Some(Phase::Pomodoro) => self.pomo_dur += extra,
+            Some(Phase::Break) => self.break_dur += extra,
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn capture(&mut self, text: &str) -> Result<(), String> {
+        match self.inbox.as_mut() {
+            Some(inbox) => inbox.capture(text).map_err(|e| e.to_string()),
+            None => Err(String::from("no inbox")),
+        }
+    }
+
+    // read the marks of the running pomodoro, other shells can add them too
+    fn refresh_marks(&mut self) {
+        let (Some(history), Some((Phase::Pomodoro, start))) = (&self.history, self.phase_start)
+        else {
+            return;
+        };
+        if self
+            .marks_checked
+            .is_some_and(|checked| checked.elapsed() < MARKS_REFRESH)
+        {
+            return;
+        }
+        self.marks_checked = Some(Instant::now());
+        match history.marks_since(start) {
+            Ok(marks) => self.marks = marks,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    fn finish_phase(&mut self, kind: Phase, outcome: Outcome) {
+        let Some((_, start)) = self.phase_start.take() else {
+            return;
+        };
+        let task = self.task.filter(|_| kind == Phase::Pomodoro);
+        let mut record = Record::new(kind, start, Local::now(), outcome);
+        record.task = task.and(self.task_name()).map(str::to_string);
+        if kind == Phase::Pomodoro && self.timer.is_flowtime() {
+            record.tags.push(String::from("flowtime"));
+        }
+        record.overtime = self.overtime.take().map(|at| at.elapsed());
+        record.extended = Some(std::mem::take(&mut self.extended)).filter(|d| !d.is_zero());
+        if kind == Phase::Pomodoro {
+            record.intention = self.intention.take().filter(|t| !t.is_empty());
+        }
+        record.interruptions = match (&self.history, kind) {
+            (Some(history), Phase::Pomodoro) => history.take_marks(start).unwrap_or_else(|e| {
+                self.message = Some(e.to_string());
+                std::mem::take(&mut self.marks)
+            }),
+            _ => std::mem::take(&mut self.marks),
+        };
+        self.marks.clear();
+        if let Some(Err(e)) = self.history.as_ref().map(|h| h.set_running(None)) {
+            self.message = Some(e.to_string());
+        }
+        // show the captures and tasks of other shells in the break
+        if let Some(Err(e)) = self.inbox.as_mut().map(|inbox| inbox.reload()) {
+            self.message = Some(e.to_string());
+        }
+        if let Some(Err(e)) = self.task_list.as_mut().map(|tasks| tasks.reload()) {
+            self.message = Some(e.to_string());
+        }
+        if let (Some(id), Outcome::Completed) = (task, outcome) {
+            if let Some(Err(e)) = self.task_list.as_mut().map(|tasks| tasks.add_pomodoro(id)) {
+                self.message = Some(e.to_string());
+            }
+        }
+        // a reflection still unanswered is saved without the rest
+        if let Some(pending) = self.pending.take() {
+            self.save_record(pending);
+            if matches!(
+                self.input.as_ref().map(|input| input.target),
+                Some(InputTarget::Reflection | InputTarget::FocusRating)
+            ) {
+                self.input = None;
+            }
+        }
+        // no reflection over a capture or a mark being typed
+        if kind == Phase::Pomodoro
+            && outcome == Outcome::Completed
+            && self.config.prompts.reflection
+            && self.input.is_none()
+        {
+            self.pending = Some(record);
+            self.ask(InputTarget::Reflection);
+            return;
+        }
+        self.save_record(record);
+    }
+
+    fn save_record(&mut self, record: Record) {
+        if let Some(history) = &self.history {
+            if let Err(e) = history.append(&record) {
+                self.message = Some(e.to_string());
+            }
+        }
+        self.records.push(record);
+    }
+
+    fn apply_setting(&mut self, text: &str) -> Result<(), String> {
+        let setting = Setting::ALL[self.setting];
+        setting.set(&mut self.config, text)?;
+        self.use_setting(setting);
+        self.message = Some(match self.config.save() {
+            Ok(()) => String::from("saved"),
+            Err(e) => e.to_string(),
+        });
+        Ok(())
+    }
+
+    // copy a value of the config into the timer
+    fn use_setting(&mut self, setting: Setting) {
+        let timer = &self.config.timer;
+        match setting {
+            Setting::Pomodoros => {
+                if let Some(n) = timer.pomodoros {
+                    self.pomo_num = n.into();
+                }
+            }
+            Setting::PomodoroDuration => {
+                if let Some(dur) = timer.pomodoro_duration {
+                    self.pomo_dur = dur;
+                    self.pomo_dur_bk = dur;
+                }
+            }
+            Setting::BreakDuration => {
+                if let Some(dur) = timer.break_duration {
+                    self.break_dur = dur;
+                    self.break_dur_bk = dur;
+                }
+            }
+            Setting::VoidAfter => self.void_after = timer.void_after,
+            Setting::AutoStartBreaks => self.timer.auto_start_breaks = timer.auto_start_breaks,
+            Setting::AutoStartPomodoros => {
+                self.timer.auto_start_pomodoros = timer.auto_start_pomodoros
+            }
+            Setting::AutoStartDelay => self.timer.auto_start_delay = timer.auto_start_delay,
+            Setting::Overtime => self.timer.overtime = timer.overtime,
+            Setting::Mode => self.timer.mode = timer.mode,
+            // like a profile, the schedule changes between phases
+            Setting::Schedule if self.phase_start.is_some() => {
+                self.message = Some(String::from("finish the phase to change the schedule"))
+            }
+            Setting::Schedule => {
+                let had_schedule = self.timer.schedule.is_some();
+                self.timer.schedule = timer.schedule.clone();
+                if had_schedule || self.timer.schedule.is_some() {
+                    self.start_schedule();
+                }
+            }
+            Setting::Theme => self.theme = Theme::from_config(&self.config),
+        }
+    }
+
+    fn apply_input(&mut self, text: &str) -> Result<(), String> {
+        match self.edit_app {
+            EditApp::PomoNum => {
+                let pomo_num = text
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a number", text.trim()))?;
+                if pomo_num == 0 {
+                    return Err(String::from("at least 1 pomodoro"));
+                }
+                self.pomo_num = pomo_num;
+            }
+            EditApp::PomoDur => {
+                self.pomo_dur = input_duration(text)?;
+                self.pomo_dur_bk = self.pomo_dur;
+            }
+            EditApp::BreakDur => {
+                self.break_dur = input_duration(text)?;
+                self.break_dur_bk = self.break_dur;
+            }
+            EditApp::Task => {
+                let (name, estimate) = input_task(text)?;
+                let tasks = self.task_list.as_mut().ok_or("the tasks can't be saved")?;
+                let task = tasks.add(name, estimate).map_err(|e| e.to_string())?;
+                self.task = Some(task.id);
+            }
+            EditApp::Nothing => (),
+        }
+        Ok(())
+    }
+
+    // one minute per press, holding the key goes faster the longer it's held
+    fn step(&mut self) -> Duration {
+        let now = Instant::now();
+        match self.last_step {
+            Some(last) if now - last < REPEAT_WINDOW => self.step_repeat += 1,
+            _ => self.step_repeat = 0,
+        }
+        self.last_step = Some(now);
+        let minutes = match self.step_repeat {
+            0..=9 => 1,
+            10..=29 => 5,
+            _ => 10,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
     fn decrement_pomo_dur(&mut self) {
-        if let Some(res) = self.pomo_dur.checked_sub(Duration::from_secs(60)) {
+        let step = self.step();
+        if let Some(res) = self.pomo_dur.checked_sub(step) {
             if res >= MIN_DURATION {
                 self.pomo_dur = res;
             }
@@ -192,13 +1439,15 @@ impl App {
     }
 
     fn increment_pomo_dur(&mut self) {
-        if let Some(res) = self.pomo_dur.checked_add(Duration::from_secs(60)) {
+        let step = self.step();
+        if let Some(res) = self.pomo_dur.checked_add(step) {
             self.pomo_dur = res;
         }
     }
 
     fn decrement_break_dur(&mut self) {
-        if let Some(res) = self.break_dur.checked_sub(Duration::from_secs(60)) {
+        let step = self.step();
+        if let Some(res) = self.break_dur.checked_sub(step) {
             if res >= MIN_DURATION {
                 self.break_dur = res;
             }
@@ -206,7 +1455,8 @@ impl App {
     }
 
     fn increment_break_dur(&mut self) {
-        if let Some(res) = self.break_dur.checked_add(Duration::from_secs(60)) {
+        let step = self.step();
+        if let Some(res) = self.break_dur.checked_add(step) {
             self.break_dur = res;
         }
     }
@@ -229,6 +1479,32 @@ impl App {
     }
 }
 
+// typed task, "Write the docs ~3" is estimated at 3 pomodoros
+pub fn input_task(text: &str) -> Result<(&str, Option<u32>), String> {
+    match text.rsplit_once('~') {
+        Some((name, estimate)) => {
+            let estimate = estimate.trim();
+            let estimate = estimate
+                .parse()
+                .map_err(|_| format!("'{}' is not a number of pomodoros", estimate))?;
+            Ok((name.trim(), Some(estimate)))
+        }
+        None => Ok((text.trim(), None)),
+    }
+}
+
+pub fn input_duration(text: &str) -> Result<Duration, String> {
+    let duration = parse_duration(text).map_err(|e| e.to_string())?;
+    if duration < MIN_DURATION {
+        return Err(String::from("at least 1 minute"));
+    }
+    Ok(duration)
+}
+
+fn emoji(shortcode: &str, fallback: &str) -> String {
+    emojis::get_by_shortcode(shortcode).map_or_else(|| fallback.to_string(), |e| e.to_string())
+}
+
 mod test {
     #[test]
     fn test_app_increment_counter() {
@@ -243,4 +1519,481 @@ mod test {
         app.decrement_counter();
         assert_eq!(app.counter, -1);
     }
+
+    #[test]
+    fn test_app_quit_records_interrupted() {
+        let mut app = crate::App::default();
+        app.toggle_start_stop();
+        app.quit();
+        assert_eq!(app.records.len(), 1);
+        assert_eq!(
+            app.records[0].outcome,
+            pomodoro_core::history::Outcome::Interrupted
+        );
+    }
+
+    #[test]
+    fn test_app_typed_duration() {
+        let mut app = crate::App::default();
+        app.edit_app = crate::app::EditApp::PomoDur;
+        app.start_input(Some('2'));
+        app.push_input('5');
+        app.push_input('m');
+        app.submit_input();
+        assert!(app.input.is_none());
+        assert_eq!(app.pomo_dur, std::time::Duration::from_secs(25 * 60));
+        assert_eq!(app.pomo_dur_bk, app.pomo_dur);
+    }
+
+    #[test]
+    fn test_app_typed_duration_error() {
+        let mut app = crate::App::default();
+        app.edit_app = crate::app::EditApp::BreakDur;
+        app.start_input(Some('3'));
+        app.push_input('0');
+        app.push_input('s');
+        app.submit_input();
+        assert!(app.input.as_ref().unwrap().error.is_some());
+        assert_eq!(app.break_dur, std::time::Duration::from_secs(15 * 60));
+    }
+
+    #[test]
+    fn test_app_typed_zero_pomodoros() {
+        let mut app = crate::App::default();
+        let pomo_num = app.pomo_num;
+        app.edit_app = crate::app::EditApp::PomoNum;
+        app.start_input(Some('0'));
+        app.submit_input();
+        assert!(app.input.as_ref().unwrap().error.is_some());
+        assert_eq!(app.pomo_num, pomo_num);
+    }
+
+    #[test]
+    fn test_app_task_pomodoro() {
+        let path = std::env::temp_dir().join("pomodoro-tui-tasks-test.json");
+        let _ = std::fs::remove_file(&path);
+        let mut app = crate::App::default();
+        app.set_tasks(pomodoro_core::tasks::TaskList::load(&path).unwrap());
+        app.edit_app = crate::app::EditApp::Task;
+        app.start_input(None);
+        "Write the docs ~3".chars().for_each(|c| app.push_input(c));
+        app.submit_input();
+        assert_eq!(app.task_name(), Some("Write the docs"));
+
+        app.toggle_start_stop();
+        app.pomo_dur = std::time::Duration::ZERO;
+        app.update_timer();
+        assert_eq!(app.records[0].task.as_deref(), Some("Write the docs"));
+        let tasks = app.pending_tasks();
+        assert_eq!((tasks[0].estimate, tasks[0].actual), (Some(3), 1));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_app_interruptions() {
+        use pomodoro_core::history::InterruptionKind;
+        let mut app = crate::App::default();
+        app.start_mark(InterruptionKind::Internal);
+        assert!(app.input.is_none());
+
+        app.toggle_start_stop();
+        app.start_mark(InterruptionKind::External);
+        "phone".chars().for_each(|c| app.push_input(c));
+        app.submit_input();
+        app.start_mark(InterruptionKind::Internal);
+        app.submit_input();
+        assert_eq!(app.marks.len(), 2);
+
+        app.quit();
+        let record = &app.records[0];
+        assert_eq!(record.interruptions_of(InterruptionKind::Internal), 1);
+        assert_eq!(record.interruptions[0].note.as_deref(), Some("phone"));
+    }
+
+    #[test]
+    fn test_app_void_pomodoro() {
+        use pomodoro_core::history::Outcome;
+        use pomodoro_core::history::Phase;
+        let mut app = crate::App::default();
+        app.toggle_start_stop();
+        app.toggle_start_stop();
+        app.check_pause();
+        assert!(app.in_pomodoro());
+
+        app.void_after = Some(std::time::Duration::ZERO);
+        app.check_pause();
+        assert!(!app.in_pomodoro());
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Stopped(Phase::Pomodoro)
+        ));
+        assert_eq!(app.pomo_dur, app.pomo_dur_bk);
+        assert_eq!(app.records[0].outcome, Outcome::Voided);
+    }
+
+    #[test]
+    fn test_app_inbox() {
+        use pomodoro_core::history::Phase;
+        use pomodoro_core::inbox::Inbox;
+        use pomodoro_core::tasks::TaskList;
+        let dir = std::env::temp_dir().join("pomodoro-tui-inbox-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut app = crate::App::default();
+        app.set_inbox(Inbox::load(dir.join("inbox.json")).unwrap());
+        app.set_tasks(TaskList::load(dir.join("tasks.json")).unwrap());
+
+        app.start_capture();
+        app.submit_input();
+        assert!(app.input.as_ref().unwrap().error.is_some());
+        app.input = None;
+        for text in ["call the bank", "buy milk"] {
+            app.start_capture();
+            text.chars().for_each(|c| app.push_input(c));
+            app.submit_input();
+        }
+        assert!(!app.showing_inbox());
+
+        app.state = crate::app::AppState::Stopped(Phase::Break);
+        assert!(app.showing_inbox());
+        app.discard_capture();
+        app.capture_to_task();
+        assert!(!app.showing_inbox());
+        assert_eq!(app.task_name(), Some("buy milk"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_app_prompts() {
+        let mut app = crate::App::default();
+        app.config.prompts.intention = true;
+        app.config.prompts.reflection = true;
+        app.toggle_start_stop();
+        assert!(!app.in_pomodoro());
+        "write the parser".chars().for_each(|c| app.push_input(c));
+        app.submit_input();
+        assert!(app.in_pomodoro());
+
+        app.pomo_dur = std::time::Duration::ZERO;
+        app.update_timer();
+        assert!(app.records.is_empty());
+        "parser done".chars().for_each(|c| app.push_input(c));
+        app.submit_input();
+        app.push_input('7');
+        app.submit_input();
+        assert!(app.input.as_ref().unwrap().error.is_some());
+        app.pop_input();
+        app.push_input('4');
+        app.submit_input();
+        assert!(app.input.is_none());
+
+        let record = &app.records[0];
+        assert_eq!(record.intention.as_deref(), Some("write the parser"));
+        assert_eq!(record.reflection.as_deref(), Some("parser done"));
+        assert_eq!(record.focus_rating, Some(4));
+
+        // an unanswered reflection is saved when the next pomodoro ends
+        use pomodoro_core::history::{Outcome, Phase};
+        app.phase_start = Some((Phase::Pomodoro, chrono::Local::now()));
+        app.finish_phase(Phase::Pomodoro, Outcome::Completed);
+        app.phase_start = Some((Phase::Pomodoro, chrono::Local::now()));
+        app.finish_phase(Phase::Pomodoro, Outcome::Completed);
+        assert_eq!(app.records.len(), 2);
+        assert_eq!(
+            app.pending.as_ref().map(|r| r.reflection.is_none()),
+            Some(true)
+        );
+
+        // no reflection over a capture
+        app.cancel_input();
+        app.start_capture();
+        app.phase_start = Some((Phase::Pomodoro, chrono::Local::now()));
+        app.finish_phase(Phase::Pomodoro, Outcome::Completed);
+        assert_eq!(app.records.len(), 4);
+        assert!(app.pending.is_none());
+    }
+
+    #[test]
+    fn test_app_profiles() {
+        use std::time::Duration;
+        let minutes = |n: u64| Some(Duration::from_secs(n * 60));
+        let mut config = pomodoro_core::config::Config::default();
+        let quick = pomodoro_core::config::TimerConfig {
+            pomodoros: Some(3),
+            pomodoro_duration: minutes(15),
+            break_duration: minutes(3),
+            long_break_duration: minutes(20),
+            long_break_every: Some(1),
+            ..Default::default()
+        };
+        config.profiles.insert(String::from("quick"), quick);
+        let mut app = crate::App::default();
+        app.set_config(config);
+        app.open_profiles();
+        app.next_profile(true);
+        app.pick_profile();
+        assert_eq!(app.profile.as_deref(), Some("quick"));
+        assert_eq!(app.pomo_num, 3);
+        assert_eq!(app.pomo_dur, Duration::from_secs(15 * 60));
+
+        app.toggle_start_stop();
+        app.pomo_dur = Duration::ZERO;
+        app.update_timer();
+        assert_eq!(app.break_dur, Duration::from_secs(20 * 60));
+    }
+
+    #[test]
+    fn test_app_auto_start() {
+        use pomodoro_core::history::Phase;
+        use std::time::Duration;
+        let mut app = crate::App::default();
+        app.timer.auto_start_breaks = Some(true);
+        app.timer.auto_start_pomodoros = Some(true);
+        app.timer.auto_start_delay = Some(Duration::from_secs(5));
+        app.pomo_num = 2;
+
+        app.toggle_start_stop();
+        app.pomo_dur = Duration::ZERO;
+        app.update_timer();
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Stopped(Phase::Break)
+        ));
+        assert!(matches!(
+            app.auto_start_in(),
+            Some((pomodoro_core::history::Phase::Break, _))
+        ));
+        assert!(app.cancel_auto_start());
+        assert!(app.auto_start_in().is_none());
+
+        // without countdown the pomodoro starts as soon as the break is over
+        app.timer.auto_start_delay = None;
+        app.toggle_start_stop();
+        app.break_dur = Duration::ZERO;
+        app.update_break_timer();
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Running(Phase::Pomodoro)
+        ));
+    }
+
+    #[test]
+    fn test_app_overtime() {
+        use pomodoro_core::history::Outcome;
+        use pomodoro_core::history::Phase;
+        use std::time::Duration;
+        let mut app = crate::App::default();
+        app.timer.overtime = Some(true);
+        app.pomo_num = 2;
+
+        app.toggle_start_stop();
+        app.pomo_dur = Duration::ZERO;
+        app.update_timer();
+        assert!(app.overtime().is_some());
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Running(Phase::Pomodoro)
+        ));
+
+        // space moves on to the break
+        app.toggle_start_stop();
+        assert!(app.overtime().is_none());
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Stopped(Phase::Break)
+        ));
+        let record = app.records.last().unwrap();
+        assert_eq!(record.outcome, Outcome::Completed);
+        assert!(record.overtime.is_some());
+    }
+
+    #[test]
+    fn test_app_skip_reset_extend() {
+        use pomodoro_core::history::Outcome;
+        use pomodoro_core::history::Phase;
+        use std::time::Duration;
+        let mut app = crate::App {
+            pomo_num: 2,
+            ..Default::default()
+        };
+        // nothing to skip before the start
+        app.skip_phase();
+        assert_eq!(app.pomo_num, 2);
+        assert!(app.records.is_empty());
+
+        app.toggle_start_stop();
+        app.start_extend();
+        app.input.as_mut().unwrap().text = String::from("5");
+        app.submit_input();
+        assert_eq!(app.pomo_dur, app.pomo_dur_bk + Duration::from_secs(5 * 60));
+        app.skip_phase();
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Stopped(Phase::Break)
+        ));
+        let record = app.records.last().unwrap();
+        assert_eq!(record.outcome, Outcome::Skipped);
+        assert_eq!(record.extended, Some(Duration::from_secs(5 * 60)));
+
+        app.toggle_start_stop();
+        app.break_dur = Duration::from_secs(10);
+        let recorded = app.records.len();
+        app.reset_phase();
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Stopped(Phase::Break)
+        ));
+        assert_eq!(app.break_dur, app.break_dur_bk);
+        // the time spent before the reset is recorded as such
+        assert_eq!(app.records.len(), recorded + 1);
+        let record = app.records.last().unwrap();
+        assert_eq!(
+            (record.kind, record.outcome),
+            (Phase::Break, Outcome::Reset)
+        );
+    }
+
+    #[test]
+    fn test_app_flowtime() {
+        use pomodoro_core::config::TimerMode;
+        use pomodoro_core::history::Phase;
+        use std::time::Duration;
+        let mut app = crate::App {
+            pomo_num: 2,
+            ..Default::default()
+        };
+        app.timer.mode = Some(TimerMode::Flowtime);
+        app.timer.flowtime.min_break = Some(Duration::from_secs(60));
+
+        app.toggle_start_stop();
+        assert!(app.focus_time().is_some());
+        app.update_timer();
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Running(Phase::Pomodoro)
+        ));
+
+        // the focus ends when the user stops, the break follows from it
+        app.toggle_start_stop();
+        assert!(matches!(
+            app.state,
+            crate::app::AppState::Stopped(Phase::Break)
+        ));
+        assert_eq!(app.break_dur, Duration::from_secs(60));
+        assert_eq!(app.records.last().unwrap().tags, vec!["flowtime"]);
+    }
+
+    #[test]
+    fn test_app_schedule() {
+        use crate::app::AppState;
+        use pomodoro_core::config::Config;
+        use pomodoro_core::history::Phase;
+        use std::time::Duration;
+        let mut config = Config::default();
+        config.timer.schedule = Some("2f 1b 3 long-break".parse().unwrap());
+        let mut app = crate::App::default();
+        app.set_config(config);
+        assert_eq!(app.state, AppState::Stopped(Phase::Pomodoro));
+        assert_eq!(app.pomo_dur, Duration::from_secs(2 * 60));
+        assert_eq!(app.pomo_num, 1);
+
+        // each step picks the next phase, two breaks in a row included
+        app.toggle_start_stop();
+        app.skip_phase();
+        assert_eq!(app.state, AppState::Stopped(Phase::Break));
+        assert_eq!(app.break_dur, Duration::from_secs(60));
+        assert_eq!(app.pomo_num, 0);
+        app.skip_phase();
+        assert_eq!(app.state, AppState::Stopped(Phase::Break));
+        assert_eq!(app.break_dur, Duration::from_secs(3 * 60));
+        assert_eq!(
+            app.schedule_step().map(|(_, index, count)| (index, count)),
+            Some((2, 3))
+        );
+        app.skip_phase();
+        assert_eq!(app.state, AppState::NoMorePomo);
+
+        // and it can run again
+        app.toggle_start_stop();
+        assert_eq!(app.state, AppState::Stopped(Phase::Pomodoro));
+        assert_eq!(app.pomo_num, 1);
+    }
+
+    #[test]
+    fn test_app_plan() {
+        use crate::app::AppState;
+        use chrono::NaiveTime;
+        use pomodoro_core::config::Config;
+        use pomodoro_core::history::Phase;
+        let mut config = Config::default();
+        config.plan.auto_start = Some(true);
+        let mut app = crate::App::default();
+        app.set_config(config);
+        assert_eq!(app.next_planned(), None);
+
+        // the pomodoro starts by itself once its time has come
+        app.planned = vec![NaiveTime::MIN];
+        app.check_plan();
+        assert_eq!(app.state, AppState::Running(Phase::Pomodoro));
+        assert_eq!(app.next_planned(), None);
+
+        // a new pomodoro length makes a new plan
+        app.pomo_dur_bk *= 2;
+        app.check_plan();
+        assert_eq!(app.planned_for.as_ref().unwrap().2, app.pomo_dur_bk);
+    }
+
+    #[test]
+    fn test_app_meeting() {
+        use crate::app::AppState;
+        use chrono::NaiveTime;
+        use pomodoro_core::history::Phase;
+        use pomodoro_core::plan::Block;
+        use std::time::Duration;
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let mut app = crate::App::default();
+        app.toggle_start_stop();
+        assert_eq!(app.state, AppState::Running(Phase::Pomodoro));
+        app.meetings = vec![Block {
+            start: at(9, 10),
+            end: at(9, 40),
+            name: Some(String::from("standup")),
+        }];
+        app.warn_meeting(at(9, 0));
+        assert!(app.message.as_ref().is_some_and(|m| m.contains("standup")));
+
+        app.fit_meeting_at(at(9, 0));
+        assert_eq!(app.pomo_dur, Duration::from_secs(10 * 60));
+        assert!(app.message.as_ref().is_some_and(|m| m.contains("09:10")));
+    }
+
+    #[test]
+    fn test_app_snooze_reminders() {
+        use chrono::{Local, NaiveTime};
+        use pomodoro_core::config::Config;
+        use pomodoro_core::reminder::ReminderConfig;
+        use std::time::Duration;
+        let mut app = crate::App::default();
+        app.snooze_reminders();
+        assert!(app
+            .message
+            .as_ref()
+            .is_some_and(|m| m.contains("no reminders")));
+
+        let mut config = Config {
+            reminder: ReminderConfig {
+                every: Some(Duration::from_secs(10 * 60)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        app.set_config(config.clone());
+        app.snooze_reminders();
+        assert!(app.message.as_ref().is_some_and(|m| m.contains("snoozed")));
+        // the snooze outlives a change of the config, not one of the reminders
+        app.set_config(config.clone());
+        assert!(app.reminders.snoozed_until(Local::now()).is_some());
+        config.reminder.from = NaiveTime::from_hms_opt(9, 0, 0);
+        app.set_config(config);
+        assert!(app.reminders.snoozed_until(Local::now()).is_none());
+    }
 }
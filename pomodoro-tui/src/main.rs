@@ -9,15 +9,31 @@ pub mod ui;
 pub mod event;
 use event::{Event, EventHandler};
 
+pub mod settings;
+
+pub mod theme;
+
 pub mod update;
-use update::{increase_timer, update};
+use update::{increase_timer, mouse, update};
 
 use ratatui::prelude::{CrosstermBackend, Terminal};
 
 use anyhow::Result;
+use pomodoro_core::config::Config;
+use pomodoro_core::history::History;
+use pomodoro_core::inbox::Inbox;
+use pomodoro_core::tasks::TaskList;
 
 fn main() -> Result<()> {
+    let config = Config::load()?;
     let mut app = App::new();
+    app.set_config(config);
+    app.set_history(History::open()?);
+    app.set_tasks(TaskList::open()?);
+    app.set_inbox(Inbox::open()?);
+    if std::env::args().any(|arg| arg == "--ascii") || !pomodoro_core::supports_unicode() {
+        app.use_ascii();
+    }
 
     let backend = CrosstermBackend::new(std::io::stderr());
     let terminal = Terminal::new(backend)?;
@@ -34,7 +50,7 @@ fn main() -> Result<()> {
         match tui.events.next()? {
             Event::Tick => increase_timer(&mut app),
             Event::Key(key_event) => update(&mut app, key_event),
-            Event::Mouse(_) => {}
+            Event::Mouse(mouse_event) => mouse(&mut app, mouse_event),
             Event::Resize(_, _) => {}
         };
     }
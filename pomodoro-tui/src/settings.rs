@@ -0,0 +1,182 @@
+use pomodoro_core::config::{Config, TimerMode};
+use pomodoro_core::duration::format_duration;
+use pomodoro_core::schedule::ScheduleError;
+
+use crate::app::input_duration;
+use crate::theme::Theme;
+
+/// Entries of the settings screen, all of them are stored in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Pomodoros,
+    PomodoroDuration,
+    BreakDuration,
+    VoidAfter,
+    AutoStartBreaks,
+    AutoStartPomodoros,
+    AutoStartDelay,
+    Overtime,
+    Mode,
+    Schedule,
+    Theme,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 11] = [
+        Setting::Pomodoros,
+        Setting::PomodoroDuration,
+        Setting::BreakDuration,
+        Setting::VoidAfter,
+        Setting::AutoStartBreaks,
+        Setting::AutoStartPomodoros,
+        Setting::AutoStartDelay,
+        Setting::Overtime,
+        Setting::Mode,
+        Setting::Schedule,
+        Setting::Theme,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::Pomodoros => "Number of pomodoros",
+            Setting::PomodoroDuration => "Pomodoro duration",
+            Setting::BreakDuration => "Break duration",
+            Setting::VoidAfter => "Void pomodoros paused for",
+            Setting::AutoStartBreaks => "Start breaks by themselves",
+            Setting::AutoStartPomodoros => "Start pomodoros by themselves",
+            Setting::AutoStartDelay => "Countdown before starting",
+            Setting::Overtime => "Count overtime",
+            Setting::Mode => "Mode (pomodoro or flowtime)",
+            Setting::Schedule => "Schedule, like (25f 5b)x3 25f 20lb",
+            Setting::Theme => "Theme",
+        }
+    }
+
+    // current value, `None` when the config doesn't set it
+    pub fn value(self, config: &Config) -> Option<String> {
+        match self {
+            Setting::Pomodoros => config.timer.pomodoros.map(|n| n.to_string()),
+            Setting::PomodoroDuration => config.timer.pomodoro_duration.map(format_duration),
+            Setting::BreakDuration => config.timer.break_duration.map(format_duration),
+            Setting::VoidAfter => config.timer.void_after.map(format_duration),
+            Setting::AutoStartBreaks => config.timer.auto_start_breaks.map(yes_no),
+            Setting::AutoStartPomodoros => config.timer.auto_start_pomodoros.map(yes_no),
+            Setting::AutoStartDelay => config.timer.auto_start_delay.map(format_duration),
+            Setting::Overtime => config.timer.overtime.map(yes_no),
+            Setting::Mode => config.timer.mode.map(|mode| {
+                String::from(match mode {
+                    TimerMode::Pomodoro => "pomodoro",
+                    TimerMode::Flowtime => "flowtime",
+                })
+            }),
+            Setting::Schedule => config.timer.schedule.as_ref().map(|s| s.to_string()),
+            Setting::Theme => config.theme.clone(),
+        }
+    }
+
+    /// Validates `text` and stores it in `config`, an empty text unsets the value.
+    pub fn set(self, config: &mut Config, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        match self {
+            Setting::Pomodoros => {
+                config.timer.pomodoros = match text {
+                    "" => None,
+                    _ => Some(
+                        text.parse()
+                            .map_err(|_| format!("'{}' is not a number", text))?,
+                    ),
+                }
+            }
+            Setting::PomodoroDuration => {
+                config.timer.pomodoro_duration = match text {
+                    "" => None,
+                    _ => Some(input_duration(text)?),
+                }
+            }
+            Setting::BreakDuration => {
+                config.timer.break_duration = match text {
+                    "" => None,
+                    _ => Some(input_duration(text)?),
+                }
+            }
+            Setting::VoidAfter => {
+                config.timer.void_after = match text {
+                    "" => None,
+                    _ => Some(input_duration(text)?),
+                }
+            }
+            Setting::AutoStartBreaks => config.timer.auto_start_breaks = input_bool(text)?,
+            Setting::AutoStartPomodoros => config.timer.auto_start_pomodoros = input_bool(text)?,
+            Setting::AutoStartDelay => {
+                config.timer.auto_start_delay = match text {
+                    "" => None,
+                    _ => Some(input_duration(text)?),
+                }
+            }
+            Setting::Overtime => config.timer.overtime = input_bool(text)?,
+            Setting::Mode => {
+                config.timer.mode = match text.to_lowercase().as_str() {
+                    "" => None,
+                    "pomodoro" => Some(TimerMode::Pomodoro),
+                    "flowtime" => Some(TimerMode::Flowtime),
+                    _ => return Err(format!("'{}' is not pomodoro or flowtime", text)),
+                }
+            }
+            Setting::Schedule => {
+                config.timer.schedule = match text {
+                    "" => None,
+                    _ => Some(text.parse().map_err(|e: ScheduleError| e.to_string())?),
+                }
+            }
+            Setting::Theme => {
+                if !text.is_empty()
+                    && Theme::builtin(text).is_none()
+                    && !config.themes.contains_key(text)
+                {
+                    return Err(format!("unknown theme '{}'", text));
+                }
+                config.theme = Some(text.to_string()).filter(|t| !t.is_empty());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    String::from(if value { "yes" } else { "no" })
+}
+
+// yes/no, on/off or true/false, `None` when empty
+fn input_bool(text: &str) -> Result<Option<bool>, String> {
+    match text.to_lowercase().as_str() {
+        "" => Ok(None),
+        "yes" | "y" | "on" | "true" => Ok(Some(true)),
+        "no" | "n" | "off" | "false" => Ok(Some(false)),
+        _ => Err(format!("'{}' is not yes or no", text)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_and_unset() {
+        let mut config = Config::default();
+        Setting::PomodoroDuration.set(&mut config, "1h5m").unwrap();
+        assert_eq!(
+            Setting::PomodoroDuration.value(&config).as_deref(),
+            Some("1h5m")
+        );
+        Setting::PomodoroDuration.set(&mut config, "").unwrap();
+        assert_eq!(Setting::PomodoroDuration.value(&config), None);
+    }
+
+    #[test]
+    fn test_unknown_theme() {
+        let mut config = Config::default();
+        assert!(Setting::Theme.set(&mut config, "solarized").is_ok());
+        assert!(Setting::Theme.set(&mut config, "nope").is_err());
+        assert_eq!(config.theme.as_deref(), Some("solarized"));
+    }
+}
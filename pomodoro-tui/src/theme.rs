@@ -0,0 +1,177 @@
+use pomodoro_core::config::{Config, ThemeConfig};
+use ratatui::style::{Color, Modifier, Style};
+
+/// Styles used to draw the interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    // top bar field being edited
+    pub active_edit: Style,
+    // clock and text while the timer is stopped
+    pub idle: Style,
+    // clock and text while the timer is running
+    pub running: Style,
+    // clock and text when there are no more pomodoros
+    pub finished: Style,
+    // clock counting past the end of a phase
+    pub overtime: Style,
+    // block borders and titles
+    pub border: Style,
+    // help line at the bottom
+    pub help: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            active_edit: Style::new().fg(Color::Green),
+            idle: Style::new().fg(Color::Yellow),
+            running: Style::new().fg(Color::Red),
+            finished: Style::new().fg(Color::Green),
+            overtime: Style::new().fg(Color::Magenta),
+            border: Style::new().fg(Color::Cyan),
+            help: Style::new().fg(Color::Gray),
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            active_edit: Style::new().fg(Color::Blue),
+            idle: Style::new().fg(Color::Magenta),
+            running: Style::new().fg(Color::Red),
+            finished: Style::new().fg(Color::Green),
+            overtime: Style::new().fg(Color::Cyan),
+            border: Style::new().fg(Color::Black),
+            help: Style::new().fg(Color::DarkGray),
+        }
+    }
+
+    pub fn solarized() -> Self {
+        Theme {
+            active_edit: Style::new().fg(Color::Rgb(0x85, 0x99, 0x00)),
+            idle: Style::new().fg(Color::Rgb(0xb5, 0x89, 0x00)),
+            running: Style::new().fg(Color::Rgb(0xdc, 0x32, 0x2f)),
+            finished: Style::new().fg(Color::Rgb(0x2a, 0xa1, 0x98)),
+            overtime: Style::new().fg(Color::Rgb(0xd3, 0x36, 0x82)),
+            border: Style::new().fg(Color::Rgb(0x26, 0x8b, 0xd2)),
+            help: Style::new().fg(Color::Rgb(0x93, 0xa1, 0xa1)),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        Theme {
+            active_edit: bold.fg(Color::Black).bg(Color::White),
+            idle: bold.fg(Color::LightYellow),
+            running: bold.fg(Color::LightRed),
+            finished: bold.fg(Color::LightGreen),
+            overtime: bold.fg(Color::LightMagenta),
+            border: bold.fg(Color::White),
+            help: Style::new().fg(Color::White),
+        }
+    }
+
+    // used when NO_COLOR is set, only text attributes and no colors
+    pub fn monochrome() -> Self {
+        Theme {
+            active_edit: Style::new().add_modifier(Modifier::REVERSED),
+            idle: Style::new(),
+            running: Style::new().add_modifier(Modifier::BOLD),
+            finished: Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            overtime: Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            border: Style::new(),
+            help: Style::new(),
+        }
+    }
+
+    /// Returns the built-in theme called `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Picks the theme selected in the config.
+    ///
+    /// `NO_COLOR` (https://no-color.org) always wins and unknown names fall
+    /// back to the default theme.
+    pub fn from_config(config: &Config) -> Self {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return Self::monochrome();
+        }
+        match config.theme.as_deref() {
+            Some(name) => Self::by_name(config, name),
+            None => Self::default(),
+        }
+    }
+
+    fn by_name(config: &Config, name: &str) -> Self {
+        if let Some(user) = config.themes.get(name) {
+            // a user theme can't be based on itself or on another user theme
+            let base = user
+                .base
+                .as_deref()
+                .and_then(Self::builtin)
+                .unwrap_or_default();
+            return base.with_overrides(user);
+        }
+        Self::builtin(name).unwrap_or_default()
+    }
+
+    fn with_overrides(mut self, user: &ThemeConfig) -> Self {
+        let slots = [
+            (&mut self.active_edit, &user.active_edit),
+            (&mut self.idle, &user.idle),
+            (&mut self.running, &user.running),
+            (&mut self.finished, &user.finished),
+            (&mut self.overtime, &user.overtime),
+            (&mut self.border, &user.border),
+            (&mut self.help, &user.help),
+        ];
+        for (style, color) in slots {
+            if let Some(color) = color.as_deref().and_then(|c| c.parse::<Color>().ok()) {
+                *style = style.fg(color);
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_user_theme_overrides_base() {
+        let mut config = Config {
+            theme: Some("mine".to_string()),
+            ..Default::default()
+        };
+        config.themes.insert(
+            "mine".to_string(),
+            ThemeConfig {
+                base: Some("light".to_string()),
+                running: Some("#ff0000".to_string()),
+                ..Default::default()
+            },
+        );
+        let theme = Theme::by_name(&config, "mine");
+        assert_eq!(theme.running, Style::new().fg(Color::Rgb(0xff, 0, 0)));
+        assert_eq!(theme.idle, Theme::light().idle);
+    }
+
+    #[test]
+    fn test_unknown_theme_is_default() {
+        assert_eq!(Theme::by_name(&Config::default(), "nope"), Theme::dark());
+    }
+}
@@ -1,4 +1,5 @@
-use std::{io, panic};
+use std::io::{self, Write};
+use std::panic;
 
 use anyhow::Result;
 use crossterm::{
@@ -55,6 +56,11 @@ impl Tui {
     /// [`rendering`]: crate::ui:render
     pub fn draw(&mut self, app: &mut App) -> Result<()> {
         self.terminal.draw(|frame| ui::render(app, frame))?;
+        if app.take_bell() {
+            let backend = self.terminal.backend_mut();
+            backend.write_all(b"\x07")?;
+            backend.flush()?;
+        }
         Ok(())
     }
 
@@ -1,32 +1,163 @@
 use ratatui::{
     layout::Alignment,
     prelude::{Constraint, Direction, Layout},
-    style::{Style, Stylize},
-    text::Line,
-    widgets::{Block, BorderType, Borders, Paragraph},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Bar, BarChart, BarGroup, Block, BorderType, Borders, Clear, Paragraph, Row, Sparkline,
+        Table, Tabs,
+    },
 };
 
 use crate::{app::EditApp, App};
 
-use crate::app::AppState;
+use crate::app::{AppState, InputTarget, Tab};
+use crate::settings::Setting;
 use crate::tui::Frame;
+use pomodoro_core::duration;
+use pomodoro_core::estimates::{EstimateReport, BUCKETS};
+use pomodoro_core::heatmap::{Heatmap, ASCII_SHADES, SHADES};
+use pomodoro_core::history::{InterruptionKind, Phase};
+use pomodoro_core::plan;
+use pomodoro_core::stats::{self, Summary};
 use ratatui::prelude::Rect;
+use ratatui::symbols::{bar, border};
 use std::time::Duration;
 
+// borders drawn in ascii mode
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+// bars of the charts in ascii mode, a full cell is `#`
+const ASCII_BARS: bar::Set = bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "#",
+    five_eighths: ":",
+    half: ":",
+    three_eighths: ":",
+    one_quarter: ".",
+    one_eighth: ".",
+    empty: " ",
+};
+
 struct TopBar {
     line_type: EditApp,
     text: String,
     title: String,
 }
 
+// space around each tab title and between two titles
+const TAB_PADDING: &str = " ";
+const TAB_DIVIDER: &str = "|";
+
 pub fn render(app: &App, f: &mut Frame) {
-    let layout = layout(f.area());
+    let (tabs_area, body) = split_tabs(f.area());
+    f.render_widget(tabs(app), tabs_area);
+    match app.tab {
+        Tab::Timer => render_timer(app, f, body),
+        Tab::Settings => f.render_widget(settings(app), body),
+        Tab::History => f.render_widget(history(app), body),
+        Tab::Stats => render_stats(app, f, body),
+    }
+}
+
+/// Tab under the mouse, `area` is the whole terminal.
+pub fn tab_at(area: Rect, column: u16, row: u16) -> Option<Tab> {
+    let (tabs_area, _) = split_tabs(area);
+    if row != tabs_area.y {
+        return None;
+    }
+    let mut x = tabs_area.x;
+    for tab in Tab::ALL {
+        let width = (TAB_PADDING.len() * 2 + tab.title().len()) as u16;
+        if (x..x + width).contains(&column) {
+            return Some(tab);
+        }
+        x += width + TAB_DIVIDER.len() as u16;
+    }
+    None
+}
+
+/// Top bar field under the mouse in the timer tab.
+pub fn top_bar_at(area: Rect, column: u16, row: u16) -> Option<EditApp> {
+    let layout = layout(split_tabs(area).1);
+    let fields = [EditApp::PomoNum, EditApp::PomoDur, EditApp::BreakDur];
+    layout
+        .iter()
+        .zip(fields)
+        .find(|(rect, _)| rect.contains((column, row).into()))
+        .map(|(_, field)| field)
+}
+
+/// Index in `App::pending_tasks` of the task under the mouse, `Some(None)`
+/// on the tasks panel but not on a task.
+pub fn task_at(area: Rect, column: u16, row: u16) -> Option<Option<usize>> {
+    let panel = layout(split_tabs(area).1)[6];
+    if !panel.contains((column, row).into()) {
+        return None;
+    }
+    // skip the top and the bottom borders
+    let bottom = panel.y + panel.height - 1;
+    Some(
+        row.checked_sub(panel.y + 1)
+            .filter(|_| row < bottom)
+            .map(usize::from),
+    )
+}
+
+/// Index in `Setting::ALL` of the setting under the mouse.
+pub fn setting_at(area: Rect, column: u16, row: u16) -> Option<usize> {
+    let body = split_tabs(area).1;
+    // skip the border
+    let first = body.y + 1;
+    if !(body.x..body.x + body.width).contains(&column) || row < first {
+        return None;
+    }
+    Some(usize::from(row - first)).filter(|&i| i < Setting::ALL.len())
+}
+
+// one line for the tabs, the rest for the selected tab
+fn split_tabs(area: Rect) -> (Rect, Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+    (layout[0], layout[1])
+}
+
+fn tabs(app: &App) -> Tabs<'_> {
+    Tabs::new(Tab::ALL.iter().map(|tab| tab.title()))
+        .select(app.tab.index())
+        .style(app.theme.border)
+        .highlight_style(app.theme.active_edit)
+        .padding(TAB_PADDING, TAB_PADDING)
+        .divider(TAB_DIVIDER)
+}
+
+fn render_timer(app: &App, f: &mut Frame, area: Rect) {
+    let layout = layout(area);
     f.render_widget(
         top_bar(
             app,
             &TopBar {
                 line_type: EditApp::PomoNum,
-                title: format!("Number of {}", app.pomo_emoji),
+                title: format!(
+                    "Number of {} ({}{} done today)",
+                    app.pomo_emoji,
+                    app.profile
+                        .as_ref()
+                        .map_or_else(String::new, |name| format!("{}, ", name)),
+                    stats::completed_on(&app.records, &app.calendar, app.calendar.today()).count()
+                ),
                 text: format!("{}", app.pomo_num),
             },
         ),
@@ -60,6 +191,127 @@ pub fn render(app: &App, f: &mut Frame) {
     f.render_widget(motivation_text(app), layout[3]);
     f.render_widget(center_clock(app), layout[4]);
     f.render_widget(help_paragraph(app), layout[5]);
+    if app.showing_inbox() {
+        f.render_widget(inbox(app), layout[6]);
+    } else {
+        f.render_widget(tasks(app), layout[6]);
+    }
+    if let Some(title) = input_target(app).and_then(popup_title) {
+        let area = popup_area(layout[4], 1);
+        f.render_widget(Clear, area);
+        f.render_widget(popup(app, title), area);
+    }
+    if let Some(selected) = app.profile_picker {
+        let area = popup_area(layout[4], app.config.profiles.len() as u16);
+        f.render_widget(Clear, area);
+        f.render_widget(profile_picker(app, selected), area);
+    }
+}
+
+// box of `lines` lines in the middle of `area`
+fn popup_area(area: Rect, lines: u16) -> Rect {
+    let width = area.width.saturating_sub(4).max(area.width.min(20));
+    let height = area.height.min(lines + 2);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn profile_picker(app: &App, selected: usize) -> Paragraph<'_> {
+    let lines: Vec<Line> = app
+        .config
+        .profiles
+        .keys()
+        .enumerate()
+        .map(|(i, name)| {
+            // unset values come from [timer]
+            let timer = app.config.profile(name).unwrap_or_default();
+            let setting = |dur: Option<std::time::Duration>| {
+                dur.map_or_else(|| String::from("-"), duration::format_duration)
+            };
+            let text = format!(
+                "{} {}  {} x {}/{}",
+                if i == selected { ">" } else { " " },
+                name,
+                timer
+                    .pomodoros
+                    .map_or_else(|| String::from("-"), |n| n.to_string()),
+                setting(timer.pomodoro_duration),
+                setting(timer.break_duration)
+            );
+            match i == selected {
+                true => Line::from(text).style(app.theme.active_edit),
+                false => Line::from(text).style(Style::new()),
+            }
+        })
+        .collect();
+    Paragraph::new(lines).block(
+        bordered(app, BorderType::Rounded)
+            .title("Profiles")
+            .title_style(app.theme.active_edit)
+            .border_style(app.theme.active_edit)
+            .title_bottom(Line::from(vec![
+                " enter".into(),
+                " use ".dim(),
+                "esc".into(),
+                " close ".dim(),
+            ])),
+    )
+}
+
+// question of the inputs typed in a popup
+fn popup_title(target: InputTarget) -> Option<&'static str> {
+    match target {
+        InputTarget::Capture => Some("Capture to the inbox"),
+        InputTarget::Intention => Some("What will you do?"),
+        InputTarget::Reflection => Some("What did you get done?"),
+        InputTarget::FocusRating => Some("Rate your focus, 1-5"),
+        InputTarget::Extend => Some("Extend the phase by"),
+        InputTarget::Field | InputTarget::Interruption(_) => None,
+    }
+}
+
+fn popup<'a>(app: &'a App, title: &'a str) -> Paragraph<'a> {
+    let mut block = bordered(app, BorderType::Rounded)
+        .title(title)
+        .title_style(app.theme.active_edit)
+        .border_style(app.theme.active_edit);
+    let text = app.input.as_ref().map_or("", |input| input.text.as_str());
+    if let Some(error) = app.input.as_ref().and_then(|input| input.error.as_ref()) {
+        block = block.title_bottom(Line::from(error.clone()).style(app.theme.running));
+    }
+    Paragraph::new(format!("{}_", text)).block(block)
+}
+
+// the captures waiting for the break, the first one is handled by t and d
+fn inbox(app: &App) -> Paragraph<'_> {
+    let block = bordered(app, BorderType::Thick)
+        .title(format!("Inbox ({})", app.captures().len()))
+        .title_style(Style::default())
+        .title_alignment(Alignment::Center)
+        .style(app.theme.active_edit);
+    let lines: Vec<Line> = app
+        .captures()
+        .iter()
+        .enumerate()
+        .map(|(i, capture)| {
+            let line = Line::from(format!(
+                "{} {} {}",
+                if i == 0 { ">" } else { " " },
+                capture.captured.format("%H:%M"),
+                capture.text
+            ));
+            if i == 0 {
+                line.style(app.theme.active_edit)
+            } else {
+                line.style(Style::new())
+            }
+        })
+        .collect();
+    Paragraph::new(lines).block(block)
 }
 
 fn layout(area: Rect) -> Vec<Rect> {
@@ -86,96 +338,222 @@ fn layout(area: Rect) -> Vec<Rect> {
         .direction(Direction::Vertical)
         .constraints(vec![
             Constraint::Percentage(20), // motivation area
-            Constraint::Percentage(80), // clock
+            Constraint::Percentage(80), // clock and tasks
         ])
         .split(layout[1]);
 
+    let clock_area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Percentage(65), // clock
+            Constraint::Percentage(35), // tasks
+        ])
+        .split(main_area[1]);
+
+    // the tasks come last to keep the indexes of the other areas
     top_pomo_num[..]
         .iter()
-        .chain(main_area[..].iter())
+        .chain([main_area[0], clock_area[0]].iter())
         .chain(layout[2..].iter())
+        .chain(clock_area[1..].iter())
         .copied()
         .collect()
 }
 
 fn top_bar<'a>(app: &'a App, bar_element: &'a TopBar) -> Paragraph<'a> {
     // App in edito mode
-    if app.edit_app == bar_element.line_type {
-        Paragraph::new(bar_element.text.clone())
-            .style(Style::new())
-            .alignment(Alignment::Center)
-            .block(
-                Block::default()
-                    .title(bar_element.title.clone())
-                    .title_style(Style::default())
-                    .green()
-                    .title_alignment(Alignment::Center)
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Thick),
-            )
+    let editing = app.edit_app == bar_element.line_type;
+    let style = if editing {
+        app.theme.active_edit
     } else {
-        Paragraph::new(bar_element.text.clone())
-            .style(Style::new())
-            .alignment(Alignment::Center)
-            .block(
-                Block::default()
-                    .title(bar_element.title.clone())
-                    .title_style(Style::default())
-                    .cyan()
-                    .title_alignment(Alignment::Center)
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Thick),
-            )
-    }
+        app.theme.border
+    };
+
+    let mut block = bordered(app, BorderType::Thick)
+        .title(bar_element.title.clone())
+        .title_style(Style::default())
+        .style(style)
+        .title_alignment(Alignment::Center);
+
+    let text = match app.field_input().filter(|_| editing) {
+        Some(input) => {
+            if let Some(error) = &input.error {
+                block = block.title_bottom(Line::from(error.clone()).style(app.theme.running));
+            }
+            format!("{}_", input.text)
+        }
+        None => bar_element.text.clone(),
+    };
+
+    Paragraph::new(text)
+        .style(Style::new())
+        .alignment(Alignment::Center)
+        .block(block)
 }
 
 fn center_clock<'a>(app: &'a App) -> Paragraph<'a> {
-    let style = match app.state {
-        AppState::StopPomo | AppState::StopBreak => Style::new().yellow(),
-        AppState::RunPomo | AppState::RunBreak => Style::new().red(),
-        AppState::NoMorePomo => Style::new().green(),
+    let style = match app.overtime() {
+        Some(_) => app.theme.overtime,
+        None => state_style(app),
+    };
+
+    let duration = match (app.overtime(), &app.state) {
+        // counting up past the end, until the user moves on
+        (Some(overtime), _) => format!("+{}", format_duration(&overtime)),
+        (None, AppState::Stopped(Phase::Pomodoro) | AppState::Running(Phase::Pomodoro)) => {
+            format_duration(&app.focus_time().unwrap_or(app.pomo_dur))
+        }
+        (None, AppState::Stopped(Phase::Break) | AppState::Running(Phase::Break)) => {
+            format_duration(&app.break_dur)
+        }
+        (None, AppState::NoMorePomo) => String::from("No more pomodoros!!"),
     };
 
-    let duration = match app.state {
-        AppState::StopPomo | AppState::RunPomo => format_duration(&app.pomo_dur),
-        AppState::StopBreak | AppState::RunBreak => format_duration(&app.break_dur),
-        AppState::NoMorePomo => String::from("No more pomodoros!!"),
+    // live interruption counts, like the marks on the paper sheet
+    let title = match app.in_pomodoro() {
+        true => format!(
+            "Elapsed time  {} {}  {} {}",
+            InterruptionKind::Internal.symbol(),
+            count_marks(app, InterruptionKind::Internal),
+            InterruptionKind::External.symbol(),
+            count_marks(app, InterruptionKind::External)
+        ),
+        false => match app.auto_start_in() {
+            Some((kind, left)) => format!(
+                "{} in {}s, esc to wait",
+                match kind {
+                    Phase::Pomodoro => "Pomodoro",
+                    Phase::Break => "Break",
+                },
+                left.as_millis().div_ceil(1000)
+            ),
+            None => match app.next_planned() {
+                Some(at) => format!("Next pomodoro at {}", plan::format_time(at)),
+                None => String::from("Elapsed time"),
+            },
+        },
     };
 
+    let mut block = bordered(app, BorderType::Thick)
+        .title(title)
+        .title_style(Style::default())
+        .style(app.theme.border)
+        .title_alignment(Alignment::Center);
+    if let Some((step, index, count)) = app.schedule_step() {
+        block = block.title_bottom(format!(
+            "Step {}/{}: {} {}",
+            index + 1,
+            count,
+            duration::format_duration(step.length),
+            step.kind.name()
+        ));
+    }
+
     Paragraph::new(duration)
         .alignment(Alignment::Center)
         .style(style)
-        .block(
-            Block::default()
-                .title("Elapsed time")
-                .title_style(Style::default())
-                .blue()
-                .title_alignment(Alignment::Center)
-                .borders(Borders::ALL)
-                .border_type(BorderType::Thick),
-        )
+        .block(block)
+}
+
+fn count_marks(app: &App, kind: InterruptionKind) -> usize {
+    app.marks.iter().filter(|mark| mark.kind == kind).count()
+}
+
+fn tasks(app: &App) -> Paragraph<'_> {
+    let editing = app.edit_app == EditApp::Task;
+    let mut block = bordered(app, BorderType::Thick)
+        .title("Tasks")
+        .title_style(Style::default())
+        .title_alignment(Alignment::Center)
+        .style(if editing {
+            app.theme.active_edit
+        } else {
+            app.theme.border
+        });
+
+    let mut lines: Vec<Line> = app
+        .pending_tasks()
+        .into_iter()
+        .map(|task| {
+            let selected = app.task == Some(task.id);
+            let pomodoros = match task.estimate {
+                Some(estimate) => format!("{}/{}", task.actual, estimate),
+                None => task.actual.to_string(),
+            };
+            let line = Line::from(format!(
+                "{} {} {}",
+                if selected { ">" } else { " " },
+                task.name,
+                pomodoros
+            ));
+            if selected {
+                line.style(app.theme.active_edit)
+            } else {
+                line.style(Style::new())
+            }
+        })
+        .collect();
+
+    match app.field_input().filter(|_| editing) {
+        Some(input) => {
+            if let Some(error) = &input.error {
+                block = block.title_bottom(Line::from(error.clone()).style(app.theme.running));
+            }
+            lines.push(Line::from(format!("+ {}_", input.text)).style(Style::new()));
+        }
+        None if lines.is_empty() => {
+            lines.push(Line::from("No tasks, tab here and press enter").style(app.theme.help))
+        }
+        None => {}
+    }
+    Paragraph::new(lines).block(block)
 }
 
 fn motivation_text(app: &App) -> Paragraph<'_> {
-    let style = match app.state {
-        AppState::StopPomo | AppState::StopBreak => Style::new().green(),
-        AppState::RunPomo | AppState::RunBreak => Style::new().yellow(),
-        AppState::NoMorePomo => Style::new().green(),
-    };
+    let style = state_style(app);
+
+    if let Some(input) = &app.input {
+        if let InputTarget::Interruption(kind) = input.target {
+            let text = format!("{} note: {}_", kind.symbol(), input.text);
+            return Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .style(app.theme.active_edit);
+        }
+    }
 
-    let motivation_string = match app.state {
-        AppState::StopPomo => "Time to focus, press space",
-        AppState::RunPomo => "Focus, don't look at me!!",
-        AppState::StopBreak => "Time to take a break, press space",
-        AppState::RunBreak => "Take a break, enjoy your coffe :)",
-        AppState::NoMorePomo => "0 Pomodoro Left, add more pomodoros",
+    if let Some(nag) = &app.nag {
+        let text = format!(
+            "Idle for {}, press space to focus or z to snooze reminders for {}",
+            duration::format_duration(nag.idle),
+            duration::format_duration(app.snooze_length())
+        );
+        let style = match nag.urgent {
+            true => app.theme.overtime,
+            false => style,
+        };
+        return Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(style);
+    }
+
+    let motivation_string = match (&app.state, app.task_name()) {
+        (AppState::Stopped(Phase::Pomodoro), _) => String::from("Time to focus, press space"),
+        (AppState::Running(Phase::Pomodoro), Some(task)) => {
+            format!("Focus on {}, don't look at me!!", task)
+        }
+        (AppState::Running(Phase::Pomodoro), None) => String::from("Focus, don't look at me!!"),
+        (AppState::Stopped(Phase::Break), _) => String::from("Time to take a break, press space"),
+        (AppState::Running(Phase::Break), _) => String::from("Take a break, enjoy your coffe :)"),
+        (AppState::NoMorePomo, _) if app.has_schedule() => {
+            String::from("Schedule done, press space to run it again")
+        }
+        (AppState::NoMorePomo, _) => String::from("0 Pomodoro Left, add more pomodoros"),
     };
     Paragraph::new(motivation_string)
         .alignment(Alignment::Center)
         .block(
             Block::default()
                 .title_style(Style::default())
-                .cyan()
                 .title_alignment(Alignment::Center)
                 .style(style),
         )
@@ -183,43 +561,424 @@ fn motivation_text(app: &App) -> Paragraph<'_> {
 
 fn help_paragraph(app: &App) -> Paragraph<'_> {
     let space_action = match app.state {
-        AppState::StopPomo | AppState::StopBreak => "start",
+        _ if app.overtime().is_some() => "move on",
+        AppState::Running(Phase::Pomodoro) if app.focus_time().is_some() => "take a break",
+        AppState::Stopped(_) => "start",
         _ => "stop",
     };
     let next_element = match app.edit_app {
         EditApp::Nothing => "edit pomodoro's number",
         EditApp::PomoNum => "edit pomodoro's duration",
         EditApp::PomoDur => "edit break's duration",
-        EditApp::BreakDur => "No action",
+        EditApp::BreakDur => "select the task",
+        EditApp::Task => "No action",
     };
 
+    if app.input.is_some() {
+        let example = match (input_target(app), &app.edit_app) {
+            (Some(InputTarget::Interruption(_)), _) => "phone call, or nothing",
+            (Some(InputTarget::Capture), _) => "call the bank",
+            (Some(InputTarget::Intention), _) => "write the parser, or nothing",
+            (Some(InputTarget::Reflection), _) => "parser done, tests left",
+            (Some(InputTarget::FocusRating), _) => "4, or nothing",
+            (Some(InputTarget::Extend), _) => "5 5m 90s",
+            (_, EditApp::Task) => "Write the docs ~3",
+            _ => "25 25m 1h10m 90s",
+        };
+        let help_text = Line::from(vec![
+            "enter".into(),
+            " confirm".dim(),
+            " esc".into(),
+            " cancel".dim(),
+            " e.g. ".into(),
+            example.dim(),
+        ]);
+        return help_block(app, help_text);
+    }
+
+    if app.showing_inbox() {
+        let help_text = Line::from(vec![
+            "space ".into(),
+            space_action.dim(),
+            " t".into(),
+            " make it a task".dim(),
+            " d".into(),
+            " discard".dim(),
+            " n".into(),
+            " capture".dim(),
+            " q".into(),
+            " quit".dim(),
+        ]);
+        return help_block(app, help_text);
+    }
+
+    if app.edit_app == EditApp::Task {
+        let help_text = Line::from(vec![
+            "space ".into(),
+            space_action.dim(),
+            " tab => ".into(),
+            Span::styled(next_element, app.theme.active_edit),
+            " j k".into(),
+            " select".dim(),
+            " enter".into(),
+            " new task".dim(),
+            " x".into(),
+            " done".dim(),
+            " q".into(),
+            " quit".dim(),
+        ]);
+        return help_block(app, help_text);
+    }
+
     let help_text = Line::from(vec![
         "space ".into(),
         space_action.dim(),
         " tab => ".into(),
-        next_element.yellow(),
+        Span::styled(next_element, app.theme.active_edit),
         " j".into(),
         " increase".dim(),
         " k".into(),
         " decrease".dim(),
+        " enter".into(),
+        " type a value".dim(),
+        " ' -".into(),
+        " interruption".dim(),
+        " v".into(),
+        " void".dim(),
+        " s r e".into(),
+        " skip reset extend".dim(),
+        " n".into(),
+        " capture".dim(),
+        " p".into(),
+        " profile".dim(),
+        " [ ]".into(),
+        " tabs".dim(),
         " q".into(),
         " quit".dim(),
     ]);
 
+    help_block(app, help_text)
+}
+
+fn input_target(app: &App) -> Option<InputTarget> {
+    app.input.as_ref().map(|input| input.target)
+}
+
+fn help_block<'a>(app: &App, help_text: Line<'a>) -> Paragraph<'a> {
     Paragraph::new(help_text)
-        .gray()
+        .style(app.theme.help)
         .alignment(Alignment::Center)
         .block(
-            Block::default()
+            bordered(app, BorderType::Rounded)
                 .title("Help")
-                .title_style(Style::default().cyan())
+                .title_style(app.theme.border)
                 .title_alignment(Alignment::Center)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().green())
-                .borders(Borders::ALL),
+                .border_style(app.theme.border),
         )
 }
 
+fn settings(app: &App) -> Paragraph<'_> {
+    let mut block = bordered(app, BorderType::Thick)
+        .title("Settings")
+        .title_style(app.theme.border)
+        .border_style(app.theme.border)
+        .title_bottom(Line::from(vec![
+            " j/k".into(),
+            " select".dim(),
+            " enter".into(),
+            " edit, empty for the default ".dim(),
+        ]));
+    if let Some(message) = &app.message {
+        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+
+    let lines: Vec<Line> = Setting::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, setting)| {
+            let selected = i == app.setting;
+            let value = match app.field_input().filter(|_| selected) {
+                Some(input) => match &input.error {
+                    Some(error) => format!("{}_  ({})", input.text, error),
+                    None => format!("{}_", input.text),
+                },
+                None => setting
+                    .value(&app.config)
+                    .unwrap_or_else(|| String::from("default")),
+            };
+            let line = Line::from(format!("{:<24}{}", setting.label(), value));
+            if selected {
+                line.style(app.theme.active_edit)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    Paragraph::new(lines).block(block)
+}
+
+fn history(app: &App) -> Table<'_> {
+    let rows = app
+        .records
+        .iter()
+        .rev()
+        .skip(app.history_scroll)
+        .map(|record| {
+            let kind = record.kind.as_str();
+            let outcome = record.outcome.as_str();
+            Row::new(vec![
+                record.start.format("%Y-%m-%d").to_string(),
+                record.start.format("%H:%M").to_string(),
+                record.end.format("%H:%M").to_string(),
+                kind.to_string(),
+                duration::format_duration(record.duration()),
+                record.overtime.map_or_else(String::new, |overtime| {
+                    format!("+{}", duration::format_duration(overtime))
+                }),
+                outcome.to_string(),
+                record
+                    .interruptions
+                    .iter()
+                    .map(|interruption| interruption.kind.symbol())
+                    .collect(),
+            ])
+        });
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(5),
+        Constraint::Length(5),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(11),
+        Constraint::Min(5),
+    ];
+
+    let mut block = bordered(app, BorderType::Thick)
+        .title(format!("History ({} records)", app.records.len()))
+        .title_style(app.theme.border)
+        .border_style(app.theme.border)
+        .title_bottom(Line::from(vec![" j/k".into(), " scroll ".dim()]));
+    if let Some(message) = &app.message {
+        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+
+    Table::new(rows, widths)
+        .header(
+            Row::new(vec![
+                "Date", "Start", "End", "Phase", "Length", "Overtime", "Outcome", "Marks",
+            ])
+            .style(app.theme.idle),
+        )
+        .column_spacing(2)
+        .block(block)
+}
+
+// days in the focus chart
+const CHART_DAYS: u64 = 14;
+
+fn render_stats(app: &App, f: &mut Frame, area: Rect) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(8),  // totals and today's pomodoros
+            Constraint::Min(8),     // focus per day
+            Constraint::Length(11), // heatmap
+            Constraint::Length(7),  // today by hour and estimates
+        ])
+        .split(area);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Percentage(40),
+        ])
+        .split(layout[3]);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(layout[0]);
+    let calendar = &app.calendar;
+    let today = calendar.today();
+
+    let summary = Summary::new(&app.records, calendar);
+    let rate = summary
+        .completion_rate()
+        .map_or_else(|| String::from("-"), |rate| format!("{:.0}%", rate * 100.0));
+    let totals = Paragraph::new(vec![
+        Line::from(format!(
+            "Pomodoros: {} completed, {} interrupted, {} skipped, {} voided, completion rate {}",
+            summary.completed, summary.interrupted, summary.skipped, summary.voided, rate
+        )),
+        Line::from(format!(
+            "Focus time: {} in {} days, {} of overtime",
+            duration::format_duration(summary.focus),
+            summary.active_days,
+            duration::format_duration(summary.overtime)
+        )),
+        Line::from(format!(
+            "Daily average: {:.1} pomodoros, {} of focus",
+            summary.average_completed(),
+            duration::format_duration(summary.average_focus())
+        )),
+        Line::from(format!(
+            "Today: {} pomodoros, this week: {}, this month: {}",
+            stats::completed_on(&app.records, calendar, today).count(),
+            stats::completed_by_week(&app.records, calendar)
+                .get(&calendar.week_of(today))
+                .unwrap_or(&0),
+            stats::completed_by_month(&app.records, calendar)
+                .get(&calendar.month_of(today))
+                .unwrap_or(&0)
+        )),
+        Line::from(format!(
+            "Interruptions: {} internal ('), {} external (-)",
+            summary.internal_interruptions, summary.external_interruptions
+        )),
+        Line::from(match summary.focus_rating {
+            Some(rating) => format!("Focus rating: {:.1}/5", rating),
+            None => String::from("Focus rating: -"),
+        }),
+    ])
+    .block(stats_block(app, "Stats"));
+    f.render_widget(totals, top[0]);
+
+    let today_lines: Vec<Line> = stats::completed_on(&app.records, calendar, today)
+        .map(|record| {
+            Line::from(format!(
+                "{} {}",
+                record.start.format("%H:%M"),
+                record.notes().unwrap_or_default()
+            ))
+        })
+        .collect();
+    let today_list = Paragraph::new(today_lines).block(stats_block(app, "Today"));
+    f.render_widget(today_list, top[1]);
+
+    let bars: Vec<Bar> = stats::focus_by_day(&app.records, calendar, today, CHART_DAYS)
+        .into_iter()
+        .map(|(day, focus)| {
+            Bar::default()
+                .value(focus.as_secs() / 60)
+                .label(Line::from(day.format("%d").to_string()))
+        })
+        .collect();
+    let chart = BarChart::default()
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(3)
+        .bar_gap(1)
+        .bar_set(bar_set(app))
+        .bar_style(app.theme.running)
+        .value_style(app.theme.active_edit.add_modifier(Modifier::REVERSED))
+        .block(stats_block(
+            app,
+            &format!("Focus minutes, last {} days", CHART_DAYS),
+        ));
+    f.render_widget(chart, layout[1]);
+
+    let hours = stats::completed_by_hour(&app.records, calendar, today);
+    let sparkline = Sparkline::default()
+        .data(hours)
+        .bar_set(bar_set(app))
+        .style(app.theme.finished)
+        .block(stats_block(app, "Today's pomodoros by hour, 00-23"));
+    f.render_widget(sparkline, bottom[0]);
+
+    let report = EstimateReport::new(app.tasks());
+    let title = match report.accuracy() {
+        Some(accuracy) => format!(
+            "Actual - estimated pomodoros, accuracy {:.0}%",
+            accuracy * 100.0
+        ),
+        None => String::from("Estimates, no done tasks with an estimate"),
+    };
+    let bars: Vec<Bar> = BUCKETS
+        .iter()
+        .zip(report.histogram())
+        .map(|(label, count)| Bar::default().value(count).label(Line::from(*label)))
+        .collect();
+    let estimates = BarChart::default()
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(3)
+        .bar_gap(1)
+        .bar_set(bar_set(app))
+        .bar_style(app.theme.running)
+        .value_style(app.theme.active_edit.add_modifier(Modifier::REVERSED))
+        .block(stats_block(app, &title));
+    f.render_widget(estimates, bottom[1]);
+
+    // the tasks done last first, as many as fit inside the borders
+    let rows = usize::from(bottom[2].height.saturating_sub(2));
+    let task_lines: Vec<Line> = report
+        .estimates
+        .iter()
+        .rev()
+        .take(rows)
+        .map(|estimate| {
+            Line::from(format!(
+                "{}/{} {}, {}",
+                estimate.actual,
+                estimate.estimate,
+                estimate.verdict(),
+                estimate.name
+            ))
+        })
+        .collect();
+    let tasks = Paragraph::new(task_lines).block(stats_block(app, "Done tasks"));
+    f.render_widget(tasks, bottom[2]);
+
+    // inside the borders
+    let weeks = Heatmap::weeks_for_width(layout[2].width.saturating_sub(2));
+    let shades = if app.ascii { &ASCII_SHADES } else { &SHADES };
+    let heatmap: Vec<Line> = Heatmap::new(&app.records, calendar, today, weeks)
+        .lines(shades)
+        .into_iter()
+        .map(Line::from)
+        .collect();
+    f.render_widget(
+        Paragraph::new(heatmap)
+            .style(app.theme.finished)
+            .block(stats_block(app, "Pomodoros per day")),
+        layout[2],
+    );
+}
+
+fn stats_block<'a>(app: &App, title: &str) -> Block<'a> {
+    bordered(app, BorderType::Thick)
+        .title(title.to_string())
+        .title_style(app.theme.border)
+        .border_style(app.theme.border)
+}
+
+// symbols of the bar charts and sparklines
+fn bar_set(app: &App) -> bar::Set<'static> {
+    match app.ascii {
+        true => ASCII_BARS,
+        false => bar::NINE_LEVELS,
+    }
+}
+
+// block with borders on every side, plain ascii ones in ascii mode
+fn bordered<'a>(app: &App, border_type: BorderType) -> Block<'a> {
+    let block = Block::default().borders(Borders::ALL);
+    if app.ascii {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block.border_type(border_type)
+    }
+}
+
+// style of the clock and of the motivation text for the current state
+fn state_style(app: &App) -> Style {
+    match app.state {
+        AppState::Stopped(_) => app.theme.idle,
+        AppState::Running(_) => app.theme.running,
+        AppState::NoMorePomo => app.theme.finished,
+    }
+}
+
 fn format_duration(duration: &Duration) -> String {
     format!(
         "{:02}min:{:02}sec",
@@ -227,3 +986,24 @@ fn format_duration(duration: &Duration) -> String {
         duration.as_secs() % 60,
     )
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_task_at() {
+        let area = Rect::new(0, 0, 120, 60);
+        let panel = layout(split_tabs(area).1)[6];
+        let column = panel.x + 2;
+        assert_eq!(task_at(area, column, panel.y), Some(None));
+        assert_eq!(task_at(area, column, panel.y + 1), Some(Some(0)));
+        let bottom = panel.y + panel.height - 1;
+        assert_eq!(task_at(area, column, bottom), Some(None));
+        assert_eq!(
+            task_at(area, column, bottom - 1),
+            Some(Some(usize::from(panel.height) - 3))
+        );
+        assert_eq!(task_at(area, column, bottom + 1), None);
+    }
+}
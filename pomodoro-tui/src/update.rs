@@ -1,31 +1,173 @@
-use crate::app::{App, AppState};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::app::{App, AppState, EditApp, Tab};
+use crate::ui;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use pomodoro_core::history::{InterruptionKind, Phase};
+use ratatui::layout::Rect;
 
 pub fn update(app: &mut App, key_event: KeyEvent) {
+    if app.input.is_some() {
+        update_input(app, key_event);
+        return;
+    }
+    if app.profile_picker.is_some() {
+        update_profiles(app, key_event);
+        return;
+    }
     match key_event.code {
+        KeyCode::Esc if app.cancel_auto_start() => {}
         KeyCode::Esc | KeyCode::Char('q') => app.quit(),
         KeyCode::Char('c') | KeyCode::Char('C') => {
             if key_event.modifiers == KeyModifiers::CONTROL {
                 app.quit()
             }
         }
+        KeyCode::F(n @ 1..=4) => app.select_tab(Tab::ALL[usize::from(n) - 1]),
+        KeyCode::Char(']') => app.next_tab(),
+        KeyCode::Char('[') => app.prev_tab(),
+        _ => match app.tab {
+            Tab::Timer => update_timer(app, key_event),
+            Tab::Settings => update_settings(app, key_event),
+            Tab::History => update_history(app, key_event),
+            Tab::Stats => {}
+        },
+    }
+}
+
+fn update_timer(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
         KeyCode::Left | KeyCode::Char('j') => app.increment(),
         KeyCode::Right | KeyCode::Char('k') => app.decrement(),
         KeyCode::Tab => app.next_edit(),
         KeyCode::Char(' ') => app.toggle_start_stop(),
+        KeyCode::Enter => app.start_input(None),
+        KeyCode::Char('x') if app.edit_app == EditApp::Task => app.finish_task(),
+        KeyCode::Char('v') => app.void_pomodoro(),
+        KeyCode::Char('s') => app.skip_phase(),
+        KeyCode::Char('r') => app.reset_phase(),
+        KeyCode::Char('e') => app.start_extend(),
+        KeyCode::Char('m') => app.fit_meeting(),
+        KeyCode::Char('z') => app.snooze_reminders(),
+        KeyCode::Char('n') => app.start_capture(),
+        KeyCode::Char('p') => app.open_profiles(),
+        KeyCode::Char('t') if app.showing_inbox() => app.capture_to_task(),
+        KeyCode::Char('d') if app.showing_inbox() => app.discard_capture(),
+        KeyCode::Char('\'') => app.start_mark(InterruptionKind::Internal),
+        KeyCode::Char('-') => app.start_mark(InterruptionKind::External),
+        KeyCode::Char(c) if c.is_ascii_digit() => app.start_input(Some(c)),
+        _ => {}
+    }
+}
+
+fn update_profiles(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
+        }
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') => app.close_profiles(),
+        KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => app.next_profile(true),
+        KeyCode::Up | KeyCode::Char('k') => app.next_profile(false),
+        KeyCode::Enter | KeyCode::Char(' ') => app.pick_profile(),
+        _ => {}
+    }
+}
+
+fn update_settings(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => app.next_setting(),
+        KeyCode::Up | KeyCode::Char('k') => app.prev_setting(),
+        KeyCode::Enter => app.start_input(None),
+        KeyCode::Char(c) if c.is_ascii_digit() => app.start_input(Some(c)),
+        _ => {}
+    }
+}
+
+fn update_history(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Down | KeyCode::Char('j') => app.scroll_history(true),
+        KeyCode::Up | KeyCode::Char('k') => app.scroll_history(false),
+        _ => {}
+    }
+}
+
+// keys while typing a value in a top bar field or in a setting
+fn update_input(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
+        }
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Enter => app.submit_input(),
+        KeyCode::Backspace => app.pop_input(),
+        KeyCode::Char(c) => app.push_input(c),
+        _ => {}
+    }
+}
+
+// click on tabs, top bar fields and settings, scroll lists
+pub fn mouse(app: &mut App, mouse_event: MouseEvent) {
+    if app.input.is_some() {
+        return;
+    }
+    let Ok((width, height)) = crossterm::terminal::size() else {
+        return;
+    };
+    let area = Rect::new(0, 0, width, height);
+    let (column, row) = (mouse_event.column, mouse_event.row);
+    match mouse_event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(tab) = ui::tab_at(area, column, row) {
+                app.select_tab(tab);
+                return;
+            }
+            match app.tab {
+                Tab::Timer => {
+                    if let Some(edit) = ui::top_bar_at(area, column, row) {
+                        app.edit_app = edit;
+                    } else if let Some(task) = ui::task_at(area, column, row) {
+                        app.edit_app = EditApp::Task;
+                        if let Some(task) = task.and_then(|i| app.pending_tasks().get(i).copied()) {
+                            app.task = Some(task.id);
+                        }
+                    }
+                }
+                Tab::Settings => {
+                    if let Some(setting) = ui::setting_at(area, column, row) {
+                        app.setting = setting;
+                    }
+                }
+                _ => {}
+            }
+        }
+        MouseEventKind::ScrollDown => match app.tab {
+            Tab::Settings => app.next_setting(),
+            Tab::History => app.scroll_history(true),
+            _ => {}
+        },
+        MouseEventKind::ScrollUp => match app.tab {
+            Tab::Settings => app.prev_setting(),
+            Tab::History => app.scroll_history(false),
+            _ => {}
+        },
         _ => {}
     }
 }
 
 pub fn increase_timer(app: &mut App) {
+    app.check_plan();
+    app.check_reminder();
     // increase timer only if we are runing
     match app.state {
-        AppState::RunPomo => {
+        AppState::Running(Phase::Pomodoro) => {
             app.update_timer();
         }
-        AppState::RunBreak => {
+        AppState::Running(Phase::Break) => {
             app.update_break_timer();
         }
+        AppState::Stopped(Phase::Pomodoro) => {
+            app.check_pause();
+            app.check_auto_start();
+        }
+        AppState::Stopped(Phase::Break) => app.check_auto_start(),
         _ => {}
     }
 }